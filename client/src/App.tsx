@@ -35,13 +35,15 @@ function App() {
         anonymous: true,
         candidates: Array.from(Array(20), (_, i) => `${i * 5}`),
         max_votes: 1,
+        round_type: "pick-one",
       };
     }
     return {
       anonymous: false,
       candidates: [],
       max_votes: 1,
-    } as RoundOpts;
+      round_type: "retro",
+    };
   }, [roundType]);
 
   const [toasts, setToasts] = useState<Toast[]>([]);
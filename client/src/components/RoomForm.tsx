@@ -54,7 +54,7 @@ export const RoomForm: FC<Props> = (props) => {
           <select value={game} name="game" onChange={(e) => setGame(e.target.value as Game)}
             style={{ padding: '0.6rem' }}
             className="flex-1 p-2 bg-ctp-text text-ctp-base placeholder-ctp-subtext0">
-            {Object.entries(Games).map(([name, value]) => <option key={value} value={value}>{name}</option>)}
+            {Object.entries(Games).map(([value, label]) => <option key={value} value={value}>{label}</option>)}
           </select>
         </div>
 
@@ -1,5 +1,13 @@
-export type Game = 'effort' | 'retro';
-export const Games: Record<string, Game> = {
-    Effort: 'effort',
-    Retro: 'retro'
+import type { GameName } from "./ppapi/GameName";
+
+export type Game = GameName;
+/** The label of every game a room can be created for, keyed by the names the server exports */
+export const Games: Record<Game, string> = {
+    'effort': 'Effort',
+    'retro': 'Retro',
+    'fist-of-five': 'Fist of five',
+    'health-check': 'Health check',
+    'lean-coffee': 'Lean Coffee',
+    'async-effort': 'Async effort',
+    'relative-sizing': 'Relative sizing'
 }
\ No newline at end of file
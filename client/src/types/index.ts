@@ -4,6 +4,7 @@ import { RoundOpts } from './ppapi/RoundOpts';
 
 export type Message = Omit<types.MessageDTO, 'date'> & { date: Date };
 export type User = types.UserDTO;
+export type Member = types.MemberDTO;
 export type Room = types.RoomDTO;
 export type Vote = types.VoteDTO;
 export type Round = types.RoundDTO;
@@ -22,20 +23,72 @@ export interface ServerToClientEvents {
     room: (room: Room) => void;
     session: (session: Session) => void;
     message: (message: types.MessageDTO) => void;
-    users: (users: User[]) => void;
+    users: (users: Member[]) => void;
     rounds: (rounds: Round[]) => void;
     votes: (votes: Vote[]) => void;
     messages: (messages: types.MessageDTO[]) => void;
     ['current round']: (currentRound: CurrentRound) => void;
     ['user updated']: (user: User) => void;
+    ['removed from room']: (roomId: string) => void;
+    nudge: (nudge: types.NudgeDTO) => void;
+    board: (board: types.RoomBoardDTO) => void;
+    actions: (actions: types.RoomActionsDTO) => void;
+    ['open actions']: (actions: types.RoomActionsDTO) => void;
+    phase: (phase: types.RoomPhaseDTO) => void;
+    ['lean coffee']: (leanCoffee: types.LeanCoffeeDTO) => void;
+    stories: (stories: types.StoriesDTO) => void;
+    sizing: (sizing: types.SizingBoardDTO) => void;
 
 }
 
 export interface ClientToServerEvents {
     ['vote']: (score: string, callback: (r: Result) => void) => void;
-    ['join']: (roomId: string, callback: (r: Result) => void) => void;
-    ['create room']: (roomName: string, game: string, callback: (r: Result) => void) => void;
+    ['retract vote']: (roomId: string, callback: (r: Result) => void) => void;
+    ['join']: (room: string | { room: string, role: types.MemberRole }, callback: (r: Result) => void) => void;
+    ['leave']: (roomId: string, callback: (r: Result) => void) => void;
+    ['set role']: (req: { room: string, role: types.MemberRole }, callback: (r: Result) => void) => void;
+    ['create room']: (...args:
+        | [roomName: string, game: string, callback: (r: Result) => void]
+        | [roomName: string, game: string, template: string | types.RetroTemplate, callback: (r: Result) => void]
+    ) => void;
     ['update user']: (user: User, callback: (r: Result) => void) => void;
     ['end vote']: (roomId: string, callback: (r: Result) => void) => void;
     ['new round']: (roomId: string, roundOpts: RoundOpts, callback: (r: Result) => void) => void;
+    ['share facilitator']: (req: { room: string, userID: string }, callback: (r: Result) => void) => void;
+    ['hand over facilitator']: (req: { room: string, userID: string }, callback: (r: Result) => void) => void;
+    ['nudge']: (roomId: string, callback: (r: Result) => void) => void;
+    ['add card']: (req: { room: string, column: string, text: string }, callback: (r: Result) => void) => void;
+    ['edit card']: (req: { room: string, cardID: string, column?: string, text: string }, callback: (r: Result) => void) => void;
+    ['delete card']: (req: { room: string, cardID: string }, callback: (r: Result) => void) => void;
+    ['group cards']: (req: { room: string, title: string, cardIDs: string[] }, callback: (r: Result) => void) => void;
+    ['move card']: (req: { room: string, cardID: string, groupID: string | null }, callback: (r: Result) => void) => void;
+    ['rename group']: (req: { room: string, groupID: string, title: string }, callback: (r: Result) => void) => void;
+    ['merge cards']: (req: { room: string, cardID: string, duplicateIDs: string[] }, callback: (r: Result) => void) => void;
+    ['start dot vote']: (req: { room: string, dots: number }, callback: (r: Result) => void) => void;
+    ['add dot']: (req: { room: string, itemID: string }, callback: (r: Result) => void) => void;
+    ['remove dot']: (req: { room: string, itemID: string }, callback: (r: Result) => void) => void;
+    ['end dot vote']: (roomId: string, callback: (r: Result) => void) => void;
+    ['create action']: (req: { room: string, text: string, ownerID?: string, due?: string }, callback: (r: Result) => void) => void;
+    ['assign action']: (req: { room: string, actionID: string, ownerID: string | null }, callback: (r: Result) => void) => void;
+    ['complete action']: (req: { room: string, actionID: string }, callback: (r: Result) => void) => void;
+    ['reopen action']: (req: { room: string, actionID: string }, callback: (r: Result) => void) => void;
+    ['set phase']: (req: { room: string, phase: types.RetroPhase }, callback: (r: Result) => void) => void;
+    ['export rounds']: (roomId: string, callback: (r: Result) => void) => void;
+    ['propose topic']: (req: { room: string, title: string }, callback: (r: Result) => void) => void;
+    ['dot topic']: (req: { room: string, topicID: string }, callback: (r: Result) => void) => void;
+    ['undot topic']: (req: { room: string, topicID: string }, callback: (r: Result) => void) => void;
+    ['start topic']: (req: { room: string, topicID?: string, minutes?: number }, callback: (r: Result) => void) => void;
+    ['extend topic']: (req: { room: string, minutes?: number }, callback: (r: Result) => void) => void;
+    ['finish topic']: (roomId: string, callback: (r: Result) => void) => void;
+    ['open story']: (req: { room: string, title: string, hours?: number }, callback: (r: Result) => void) => void;
+    ['vote story']: (req: { room: string, storyID: string, score: string }, callback: (r: Result) => void) => void;
+    ['retract story vote']: (req: { room: string, storyID: string }, callback: (r: Result) => void) => void;
+    ['reveal story']: (req: { room: string, storyID: string }, callback: (r: Result) => void) => void;
+    ['add item']: (req: { room: string, title: string }, callback: (r: Result) => void) => void;
+    ['move item']: (req: { room: string, itemID: string, bucket: string | null, index?: number }, callback: (r: Result) => void) => void;
+    ['remove item']: (req: { room: string, itemID: string }, callback: (r: Result) => void) => void;
+    ['finish sizing']: (roomId: string, callback: (r: Result) => void) => void;
+    ['reveal']: (roomId: string, callback: (r: Result) => void) => void;
+    ['remove member']: (req: { room: string, userID: string, ban?: boolean }, callback: (r: Result) => void) => void;
+    ['set deck']: (req: { room: string, deck: string[] }, callback: (r: Result) => void) => void;
 }
\ No newline at end of file
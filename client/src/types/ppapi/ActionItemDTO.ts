@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActionStatus } from "./ActionStatus";
+import type { UserDTO } from "./UserDTO";
+
+export interface ActionItemDTO { actionID: string, text: string, owner: UserDTO | null, due: string | null, status: ActionStatus, created: string, }
\ No newline at end of file
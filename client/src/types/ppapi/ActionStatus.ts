@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type Game = "Effort" | "Retro";
\ No newline at end of file
+export type ActionStatus = "open" | "done";
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MergedCard } from "./MergedCard";
+
+export interface CardDTO { cardID: string, author: string, column: string, text: string, created: string, groupID: string | null, merged: Array<MergedCard>, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HealthRating } from "./HealthRating";
+import type { Trend } from "./Trend";
+
+export interface CategoryHealth { category: string, title: string, green: number, yellow: number, red: number, rating: HealthRating | null, trend: Trend, history: Array<HealthRating | null>, }
\ No newline at end of file
@@ -1,3 +1,6 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PollDTO } from "./PollDTO";
+import type { RoundResult } from "./RoundResult";
+import type { RoundType } from "./RoundType";
 
-export interface CurrentRoundDTO { name: string, flipped: boolean, candidates: Array<string>, max_votes: number, anonymous: boolean, round_type: string, }
\ No newline at end of file
+export interface CurrentRoundDTO { name: string, flipped: boolean, candidates: Array<string>, max_votes: number, anonymous: boolean, round_type: RoundType, result: RoundResult | null, poll: PollDTO | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DotTally } from "./DotTally";
+
+export interface DotVoteDTO { dots_per_member: number, open: boolean, my_dots: Array<string>, tally: Array<DotTally>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GameName = "effort" | "retro" | "fist-of-five" | "health-check" | "lean-coffee" | "async-effort" | "relative-sizing";
\ No newline at end of file
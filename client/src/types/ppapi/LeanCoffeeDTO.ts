@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiscussionDTO } from "./DiscussionDTO";
+import type { TopicDTO } from "./TopicDTO";
+
+export interface LeanCoffeeDTO { room: string, queue: Array<TopicDTO>, discussing: TopicDTO | null, discussion: DiscussionDTO | null, done: Array<TopicDTO>, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MemberRole } from "./MemberRole";
+import type { Presence } from "./Presence";
+
+export interface MemberDTO { userID: string, name: string, email: string, avatar: string, role: MemberRole, presence: Presence, }
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CardDTO } from "./CardDTO";
+import type { CardGroup } from "./CardGroup";
+import type { DotVoteDTO } from "./DotVoteDTO";
+
+export interface RetroBoardDTO { revealed: boolean, cards: Array<CardDTO>, hidden_cards: number, groups: Array<CardGroup>, dot_vote: DotVoteDTO | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RetroColumn } from "./RetroColumn";
+
+export interface RetroTemplate { name: string, columns: Array<RetroColumn>, }
\ No newline at end of file
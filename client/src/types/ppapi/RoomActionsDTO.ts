@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActionItemDTO } from "./ActionItemDTO";
+
+export interface RoomActionsDTO { room: string, actions: Array<ActionItemDTO>, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RetroBoardDTO } from "./RetroBoardDTO";
+
+export interface RoomBoardDTO { room: string, board: RetroBoardDTO, }
\ No newline at end of file
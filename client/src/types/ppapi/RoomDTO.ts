@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
-import type { Game } from "./Game";
+import type { GameName } from "./GameName";
+import type { RetroTemplate } from "./RetroTemplate";
 
-export interface RoomDTO { roomID: string, name: string, game: Game, }
\ No newline at end of file
+export interface RoomDTO { roomID: string, name: string, game: GameName, facilitators: Array<string>, template: RetroTemplate | null, deck: Array<string>, }
\ No newline at end of file
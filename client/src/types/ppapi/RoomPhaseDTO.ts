@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RetroPhase } from "./RetroPhase";
+
+export interface RoomPhaseDTO { room: string, phase: RetroPhase, }
\ No newline at end of file
@@ -1,4 +1,6 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RetroBoardDTO } from "./RetroBoardDTO";
+import type { RoundResult } from "./RoundResult";
 import type { VoteDTO } from "./VoteDTO";
 
-export interface RoundDTO { name: string, votes: Array<VoteDTO>, }
\ No newline at end of file
+export interface RoundDTO { name: string, votes: Array<VoteDTO>, result: RoundResult | null, board: RetroBoardDTO | null, }
\ No newline at end of file
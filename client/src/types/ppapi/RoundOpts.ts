@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PollOpts } from "./PollOpts";
 
-export interface RoundOpts { candidates: Array<string>, max_votes: number, anonymous: boolean, round_type: string, }
\ No newline at end of file
+export interface RoundOpts { candidates: Array<string>, max_votes: number, anonymous: boolean, round_type: string, poll?: PollOpts, }
\ No newline at end of file
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CandidateAllocation } from "./CandidateAllocation";
+import type { CandidatePoints } from "./CandidatePoints";
+import type { CandidateTally } from "./CandidateTally";
+import type { CategoryHealth } from "./CategoryHealth";
+import type { Concern } from "./Concern";
+import type { RunoffRound } from "./RunoffRound";
+
+export type RoundResult = { "type": "estimate", average: number | null, tally: Array<CandidateTally>, } | { "type": "pick-one", tally: Array<CandidateTally>, } | { "type": "multi-pick", tally: Array<CandidateTally>, } | { "type": "percent", allocations: Array<CandidateAllocation>, } | { "type": "ranking", winner: string | null, runoff: Array<RunoffRound>, borda: Array<CandidatePoints>, } | { "type": "retro", tally: Array<CandidateTally>, } | { "type": "fist-of-five", average: number | null, distribution: Array<CandidateTally>, concerns: Array<Concern>, } | { "type": "health-check", categories: Array<CategoryHealth>, } | { "type": "thumbs", up: number, sideways: number, down: number, keep_going: boolean, } | { "type": "poll", tally: Array<CandidateTally>, answers: Array<string>, } | { "type": "sized", estimate: string, };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type RoundType = "estimate" | "pick-one" | "multi-pick" | "percent" | "ranking" | "retro" | "thumbs" | "poll";
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SizingBucketDTO } from "./SizingBucketDTO";
+import type { SizingItemDTO } from "./SizingItemDTO";
+
+export interface SizingBoardDTO { room: string, unplaced: Array<SizingItemDTO>, buckets: Array<SizingBucketDTO>, }
\ No newline at end of file
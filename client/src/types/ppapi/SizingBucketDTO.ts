@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SizingItemDTO } from "./SizingItemDTO";
+
+export interface SizingBucketDTO { candidate: string, items: Array<SizingItemDTO>, }
\ No newline at end of file
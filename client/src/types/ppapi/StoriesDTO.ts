@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StoryDTO } from "./StoryDTO";
+
+export interface StoriesDTO { room: string, open: Array<StoryDTO>, revealed: Array<StoryDTO>, }
\ No newline at end of file
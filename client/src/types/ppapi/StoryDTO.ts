@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CurrentRoundDTO } from "./CurrentRoundDTO";
+import type { VoteDTO } from "./VoteDTO";
+
+export interface StoryDTO { storyID: string, round: CurrentRoundDTO, opened: string, due: string, votes: Array<VoteDTO>, }
\ No newline at end of file
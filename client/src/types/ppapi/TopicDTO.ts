@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TopicStatus } from "./TopicStatus";
+
+export interface TopicDTO { topicID: string, author: string, title: string, created: string, dots: Array<string>, status: TopicStatus, }
\ No newline at end of file
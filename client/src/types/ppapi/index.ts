@@ -4,4 +4,46 @@ export * from './MessageDTO'
 export * from './RoomDTO'
 export * from './RoundDTO'
 export * from './UserDTO'
-export * from './AckResult'
\ No newline at end of file
+export * from './AckResult'
+export * from './CandidateTally'
+export * from './CandidateAllocation'
+export * from './RoundResult'
+export * from './RoundType'
+export * from './RunoffRound'
+export * from './CandidatePoints'
+export * from './MemberDTO'
+export * from './MemberRole'
+export * from './Presence'
+export * from './NudgeDTO'
+export * from './CardDTO'
+export * from './RetroBoardDTO'
+export * from './RoomBoardDTO'
+export * from './CardGroup'
+export * from './MergedCard'
+export * from './DotTally'
+export * from './DotVoteDTO'
+export * from './ActionItemDTO'
+export * from './ActionStatus'
+export * from './RoomActionsDTO'
+export * from './RetroColumn'
+export * from './RetroTemplate'
+export * from './RetroPhase'
+export * from './RoomPhaseDTO'
+export * from './ExportDTO'
+export * from './Concern'
+export * from './CategoryHealth'
+export * from './HealthRating'
+export * from './Trend'
+export * from './DiscussionDTO'
+export * from './LeanCoffeeDTO'
+export * from './Thumb'
+export * from './TopicDTO'
+export * from './TopicStatus'
+export * from './PollOpts'
+export * from './PollDTO'
+export * from './StoriesDTO'
+export * from './StoryDTO'
+export * from './SizingBoardDTO'
+export * from './SizingBucketDTO'
+export * from './SizingItemDTO'
+export * from './GameName'
\ No newline at end of file
@@ -2,16 +2,22 @@ use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 use serde::ser::SerializeTuple;
 use serde::Serializer;
+use crate::state::member::{MemberDTO, MemberRole};
 use crate::state::message::MessageDTO;
+use crate::state::lean_coffee::LeanCoffeeDTO;
+use crate::state::retro::{RetroPhase, RoomActionsDTO, RoomBoardDTO, RoomPhaseDTO};
 use crate::state::room::RoomDTO;
 use crate::state::round::{CurrentRoundDTO, RoundDTO};
+use crate::state::sizing::SizingBoardDTO;
+use crate::state::story::StoriesDTO;
+use crate::state::template::RetroTemplate;
 use crate::state::user::UserDTO;
-use crate::state::vote::VoteDTO;
+use crate::state::vote::{NudgeDTO, VoteDTO};
 
 pub enum ServerEvent<'a> {
     Message(&'a MessageDTO),
     Messages(&'a Vec<MessageDTO>),
-    Users(&'a Vec<UserDTO>),
+    Users(&'a Vec<MemberDTO>),
     User(&'a UserDTO),
     UserUpdated(&'a UserDTO),
     Room(&'a RoomDTO),
@@ -19,30 +25,117 @@ pub enum ServerEvent<'a> {
     Vote(&'a VoteDTO),
     Rounds(&'a Vec<RoundDTO>),
     CurrentRound(&'a CurrentRoundDTO),
+    RemovedFromRoom(&'a String),
+    Nudge(&'a NudgeDTO),
+    Board(&'a RoomBoardDTO),
+    Actions(&'a RoomActionsDTO),
+    OpenActions(&'a RoomActionsDTO),
+    Phase(&'a RoomPhaseDTO),
+    LeanCoffee(&'a LeanCoffeeDTO),
+    Stories(&'a StoriesDTO),
+    Sizing(&'a SizingBoardDTO),
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ClientEvent {
     Join,
+    Leave,
+    SetRole,
     // UpdateUser(UserDTO),
     UpdateUser,
     Vote,
+    RetractVote,
     Reveal,
     NewRound,
     CreateRoom,
     EndVote,
+    ShareFacilitator,
+    HandOverFacilitator,
+    RemoveMember,
+    SetDeck,
+    Nudge,
+    AddCard,
+    EditCard,
+    DeleteCard,
+    GroupCards,
+    MoveCard,
+    RenameGroup,
+    MergeCards,
+    StartDotVote,
+    AddDot,
+    RemoveDot,
+    EndDotVote,
+    CreateAction,
+    AssignAction,
+    CompleteAction,
+    ReopenAction,
+    SetPhase,
+    ExportRounds,
+    ProposeTopic,
+    DotTopic,
+    UndotTopic,
+    StartTopic,
+    ExtendTopic,
+    FinishTopic,
+    OpenStory,
+    VoteStory,
+    RetractStoryVote,
+    RevealStory,
+    AddItem,
+    MoveItem,
+    RemoveItem,
+    FinishSizing,
 }
 
 impl ClientEvent {
     pub fn as_str(&self) -> &'static str {
         match self {
             ClientEvent::Join => "join",
+            ClientEvent::Leave => "leave",
+            ClientEvent::SetRole => "set role",
             ClientEvent::UpdateUser => "update user",
             ClientEvent::Vote => "vote",
+            ClientEvent::RetractVote => "retract vote",
             ClientEvent::Reveal => "reveal",
             ClientEvent::NewRound => "new round",
             ClientEvent::CreateRoom => "create room",
             ClientEvent::EndVote => "end vote",
+            ClientEvent::ShareFacilitator => "share facilitator",
+            ClientEvent::HandOverFacilitator => "hand over facilitator",
+            ClientEvent::RemoveMember => "remove member",
+            ClientEvent::SetDeck => "set deck",
+            ClientEvent::Nudge => "nudge",
+            ClientEvent::AddCard => "add card",
+            ClientEvent::EditCard => "edit card",
+            ClientEvent::DeleteCard => "delete card",
+            ClientEvent::GroupCards => "group cards",
+            ClientEvent::MoveCard => "move card",
+            ClientEvent::RenameGroup => "rename group",
+            ClientEvent::MergeCards => "merge cards",
+            ClientEvent::StartDotVote => "start dot vote",
+            ClientEvent::AddDot => "add dot",
+            ClientEvent::RemoveDot => "remove dot",
+            ClientEvent::EndDotVote => "end dot vote",
+            ClientEvent::CreateAction => "create action",
+            ClientEvent::AssignAction => "assign action",
+            ClientEvent::CompleteAction => "complete action",
+            ClientEvent::ReopenAction => "reopen action",
+            ClientEvent::SetPhase => "set phase",
+            ClientEvent::ExportRounds => "export rounds",
+            ClientEvent::ProposeTopic => "propose topic",
+            ClientEvent::DotTopic => "dot topic",
+            ClientEvent::UndotTopic => "undot topic",
+            ClientEvent::StartTopic => "start topic",
+            ClientEvent::ExtendTopic => "extend topic",
+            ClientEvent::FinishTopic => "finish topic",
+            ClientEvent::OpenStory => "open story",
+            ClientEvent::VoteStory => "vote story",
+            ClientEvent::RetractStoryVote => "retract story vote",
+            ClientEvent::RevealStory => "reveal story",
+            ClientEvent::AddItem => "add item",
+            ClientEvent::MoveItem => "move item",
+            ClientEvent::RemoveItem => "remove item",
+            ClientEvent::FinishSizing => "finish sizing",
         }
     }
 }
@@ -79,6 +172,15 @@ impl<'a> serde::Serialize for ServerEvent<'a> {
             ServerEvent::Rounds(p) => tup.serialize_element(p),
             ServerEvent::User(p) => tup.serialize_element(p),
             ServerEvent::Vote(p) => tup.serialize_element(p),
+            ServerEvent::RemovedFromRoom(p) => tup.serialize_element(p),
+            ServerEvent::Nudge(p) => tup.serialize_element(p),
+            ServerEvent::Board(p) => tup.serialize_element(p),
+            ServerEvent::Actions(p) => tup.serialize_element(p),
+            ServerEvent::OpenActions(p) => tup.serialize_element(p),
+            ServerEvent::Phase(p) => tup.serialize_element(p),
+            ServerEvent::LeanCoffee(p) => tup.serialize_element(p),
+            ServerEvent::Stories(p) => tup.serialize_element(p),
+            ServerEvent::Sizing(p) => tup.serialize_element(p),
         }?;
         tup.end()
     }
@@ -97,6 +199,15 @@ impl<'a> ServerEvent<'a> {
             ServerEvent::Rounds(_) => "rounds",
             ServerEvent::User(_) => "user",
             ServerEvent::Vote(_) => "vote",
+            ServerEvent::RemovedFromRoom(_) => "removed from room",
+            ServerEvent::Nudge(_) => "nudge",
+            ServerEvent::Board(_) => "board",
+            ServerEvent::Actions(_) => "actions",
+            ServerEvent::OpenActions(_) => "open actions",
+            ServerEvent::Phase(_) => "phase",
+            ServerEvent::LeanCoffee(_) => "lean coffee",
+            ServerEvent::Stories(_) => "stories",
+            ServerEvent::Sizing(_) => "sizing",
         }
     }
 }
@@ -107,6 +218,257 @@ pub struct MessageIn {
     pub content: String,
 }
 
+/// The room name and game, optionally followed by the retro template to use
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum CreateRoomIn {
+    Room(String, String),
+    WithTemplate(String, String, TemplateIn),
+}
+
+impl CreateRoomIn {
+    pub fn into_parts(self) -> (String, String, Option<TemplateIn>) {
+        match self {
+            CreateRoomIn::Room(name, game) => (name, game, None),
+            CreateRoomIn::WithTemplate(name, game, template) => (name, game, Some(template)),
+        }
+    }
+}
+
+/// Either the name of a built-in retro template or a user-defined one
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum TemplateIn {
+    Named(String),
+    Custom(RetroTemplate),
+}
+
+/// Either just the room ID, or the room ID along with the role to join as
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum JoinIn {
+    Room(String),
+    WithRole { room: String, role: MemberRole },
+}
+
+impl JoinIn {
+    pub fn into_parts(self) -> (String, Option<MemberRole>) {
+        match self {
+            JoinIn::Room(room) => (room, None),
+            JoinIn::WithRole { room, role } => (room, Some(role)),
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RoleIn {
+    pub room: String,
+    pub role: MemberRole,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FacilitatorIn {
+    pub room: String,
+    #[serde(rename = "userID")]
+    pub user_id: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RemoveMemberIn {
+    pub room: String,
+    #[serde(rename = "userID")]
+    pub user_id: String,
+    #[serde(default)]
+    pub ban: bool,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct DeckIn {
+    pub room: String,
+    pub deck: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CardIn {
+    pub room: String,
+    pub column: String,
+    pub text: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CardEditIn {
+    pub room: String,
+    #[serde(rename = "cardID")]
+    pub card_id: String,
+    pub column: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CardRefIn {
+    pub room: String,
+    #[serde(rename = "cardID")]
+    pub card_id: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GroupCardsIn {
+    pub room: String,
+    pub title: String,
+    #[serde(rename = "cardIDs")]
+    pub card_ids: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct MoveCardIn {
+    pub room: String,
+    #[serde(rename = "cardID")]
+    pub card_id: String,
+    #[serde(rename = "groupID")]
+    pub group_id: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RenameGroupIn {
+    pub room: String,
+    #[serde(rename = "groupID")]
+    pub group_id: String,
+    pub title: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct MergeCardsIn {
+    pub room: String,
+    #[serde(rename = "cardID")]
+    pub card_id: String,
+    #[serde(rename = "duplicateIDs")]
+    pub duplicate_ids: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct DotVoteIn {
+    pub room: String,
+    pub dots: u8,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct DotIn {
+    pub room: String,
+    #[serde(rename = "itemID")]
+    pub item_id: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ActionIn {
+    pub room: String,
+    pub text: String,
+    #[serde(rename = "ownerID")]
+    pub owner_id: Option<String>,
+    /// Due date as `YYYY-MM-DD`
+    pub due: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AssignActionIn {
+    pub room: String,
+    #[serde(rename = "actionID")]
+    pub action_id: String,
+    #[serde(rename = "ownerID")]
+    pub owner_id: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ActionRefIn {
+    pub room: String,
+    #[serde(rename = "actionID")]
+    pub action_id: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PhaseIn {
+    pub room: String,
+    pub phase: RetroPhase,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TopicIn {
+    pub room: String,
+    pub title: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TopicRefIn {
+    pub room: String,
+    #[serde(rename = "topicID")]
+    pub topic_id: String,
+}
+
+/// Starts on a topic, the top of the queue if none is given, with a timebox in minutes
+#[derive(Debug, serde::Deserialize)]
+pub struct StartTopicIn {
+    pub room: String,
+    #[serde(rename = "topicID", default)]
+    pub topic_id: Option<String>,
+    #[serde(default)]
+    pub minutes: Option<u8>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TimeboxIn {
+    pub room: String,
+    #[serde(default)]
+    pub minutes: Option<u8>,
+}
+
+/// Opens a story for asynchronous estimation with the room's deck, due in the given number of hours
+#[derive(Debug, serde::Deserialize)]
+pub struct StoryIn {
+    pub room: String,
+    pub title: String,
+    #[serde(default)]
+    pub hours: Option<u16>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct StoryRefIn {
+    pub room: String,
+    #[serde(rename = "storyID")]
+    pub story_id: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct StoryVoteIn {
+    pub room: String,
+    #[serde(rename = "storyID")]
+    pub story_id: String,
+    pub score: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ItemIn {
+    pub room: String,
+    pub title: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ItemRefIn {
+    pub room: String,
+    #[serde(rename = "itemID")]
+    pub item_id: String,
+}
+
+/// Moves an item into a bucket of the sizing board, or off the scale if none is given, at the
+/// given index among the items there
+#[derive(Debug, serde::Deserialize)]
+pub struct MoveItemIn {
+    pub room: String,
+    #[serde(rename = "itemID")]
+    pub item_id: String,
+    pub bucket: Option<String>,
+    #[serde(default)]
+    pub index: usize,
+}
+
 #[derive(Debug, serde::Deserialize)]
 pub struct VoteIn {
     pub room: String,
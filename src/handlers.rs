@@ -1,20 +1,17 @@
 use std::collections::HashMap;
-use base64::Engine;
 use serde::{Deserialize, Serialize};
 use socketioxide::extract::{AckSender, Data, SocketRef, State, TryData};
-use std::fmt::{Debug, Display};
+use std::fmt::Debug;
 use std::future::Future;
-use std::hash::Hash;
+use chrono::{DateTime, Utc};
 use tracing::{debug, error, info, info_span, warn};
 use uuid::Uuid;
 
-use crate::event::{ClientEvent, MessageIn, ServerEvent, UserIn, VoteIn};
-use crate::id::encode_id;
-use crate::state::room::{Room, RoomDTO};
-use crate::state::round::{CurrentRound, CurrentRoundDTO, RoundDTO, RoundOpts};
+use crate::event::{ClientEvent, CreateRoomIn, DeckIn, FacilitatorIn, JoinIn, MessageIn, RemoveMemberIn, RoleIn, ServerEvent, UserIn, VoteIn};
+use crate::state::game::GAMES;
+use crate::state::round::RoundOpts;
 use crate::state::user::UserDTO;
-use crate::state::vote::VoteDTO;
-use crate::state::{Message, RoomState, Round, Session, Sessions, User, Users, Vote};
+use crate::state::{Message, Room, RoomState, Session, Sessions, User, Users};
 use crate::{pokemon, state};
 
 pub type EventResult = Result<(), String>;
@@ -27,25 +24,6 @@ pub struct Auth {
     user_id: Option<String>,
 }
 
-/// Request/Response Types
-#[derive(Debug, Serialize, Clone)]
-struct UserConnectedRes {
-    #[serde(rename = "userID")]
-    user_id: String,
-    connected: bool,
-    user: UserDTO,
-}
-
-impl UserConnectedRes {
-    fn new(session: &Session, user: UserDTO) -> Self {
-        Self {
-            user_id: session.user_id.clone(),
-            connected: session.connected,
-            user,
-        }
-    }
-}
-
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct PrivateMessageReq {
     to: Uuid,
@@ -57,6 +35,7 @@ pub async fn on_connection(
     TryData(auth): TryData<Auth>,
     State(sessions): State<Sessions>,
     State(users_state): State<Users>,
+    State(room_state): State<RoomState>,
 ) {
     let span = info_span!("Socket Connection", socket = %s.id);
     let _guard = span.enter();
@@ -67,25 +46,99 @@ pub async fn on_connection(
         return;
     }
 
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    rooms::broadcast_presence(&s, &user_id, room_state, &users_state.0).await;
+
     s.on(
         ClientEvent::CreateRoom,
         |socket: SocketRef,
-         Data::<(String, String)>((room_name, game_name)),
+         Data::<CreateRoomIn>(create),
          room_state: State<RoomState>,
          ack_sender: AckSender| async move {
+            let (room_name, game_name, template) = create.into_parts();
             info!(socket = %socket.id, event = %ClientEvent::CreateRoom, room_name, "Received event");
-            ack_result(ack_sender, rooms::handle_create(&socket, room_name, game_name, room_state).await)
+            ack_result(ack_sender, rooms::handle_create(&socket, room_name, game_name, template, room_state).await)
         });
 
     s.on(
         ClientEvent::Join,
+        |socket: SocketRef,
+         Data::<JoinIn>(join),
+         room_state: State<RoomState>,
+         State(Users(users_state)),
+         ack_sender: AckSender| async move {
+            let (room_id, role) = join.into_parts();
+            info!(socket = %socket.id, event = %ClientEvent::Join, room_id, ?role, "Received event");
+            ack_result(ack_sender, rooms::handle_join(&socket, room_id, role, room_state, users_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::Leave,
         |socket: SocketRef,
          Data::<String>(room_id),
          room_state: State<RoomState>,
          State(Users(users_state)),
          ack_sender: AckSender| async move {
-            info!(socket = %socket.id, event = %ClientEvent::Join, room_id, "Received event");
-            ack_result(ack_sender, rooms::handle_join(&socket, room_id, room_state, users_state).await);
+            info!(socket = %socket.id, event = %ClientEvent::Leave, room_id, "Received event");
+            ack_result(ack_sender, rooms::handle_leave(&socket, room_id, room_state, users_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::SetRole,
+        |socket: SocketRef,
+         Data(RoleIn { room, role }),
+         room_state: State<RoomState>,
+         State(Users(users_state)),
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::SetRole, room, ?role, "Received event");
+            ack_result(ack_sender, rooms::handle_set_role(&socket, room, role, room_state, users_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::ShareFacilitator,
+        |socket: SocketRef,
+         Data(FacilitatorIn { room, user_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::ShareFacilitator, room, user_id, "Received event");
+            ack_result(ack_sender, rooms::handle_add_facilitator(&socket, room, user_id, false, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::HandOverFacilitator,
+        |socket: SocketRef,
+         Data(FacilitatorIn { room, user_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::HandOverFacilitator, room, user_id, "Received event");
+            ack_result(ack_sender, rooms::handle_add_facilitator(&socket, room, user_id, true, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::RemoveMember,
+        |socket: SocketRef,
+         Data(RemoveMemberIn { room, user_id, ban }),
+         room_state: State<RoomState>,
+         State(Users(users_state)),
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::RemoveMember, room, user_id, ban, "Received event");
+            ack_result(ack_sender, rooms::handle_remove_member(&socket, room, user_id, ban, room_state, users_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::SetDeck,
+        |socket: SocketRef,
+         Data(DeckIn { room, deck }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::SetDeck, room, ?deck, "Received event");
+            ack_result(ack_sender, rooms::handle_set_deck(&socket, room, deck, room_state).await);
         },
     );
 
@@ -94,9 +147,21 @@ pub async fn on_connection(
         |s: SocketRef,
          Data::<(String, RoundOpts)>((room, round_opts)),
          room_state: State<RoomState>,
+         State(Users(users_state)),
          ack_sender: AckSender| async move {
             info!(socket = %s.id, event = %ClientEvent::NewRound, "Received event");
-            ack_result(ack_sender, rounds::handle_new(&s, room, round_opts, room_state).await);
+            ack_result(ack_sender, rounds::handle_new(&s, room, round_opts, room_state, users_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::ExportRounds,
+        |s: SocketRef,
+         Data::<String>(room),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %s.id, event = %ClientEvent::ExportRounds, room, "Received event");
+            ack_result(ack_sender, rounds::handle_export(&s, room, room_state).await);
         },
     );
 
@@ -133,6 +198,17 @@ pub async fn on_connection(
         },
     );
 
+    s.on(
+        ClientEvent::RetractVote,
+        |socket: SocketRef,
+         Data::<String>(room),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::RetractVote, room, "Received event");
+            ack_result(ack_sender, votes::handle_retract_vote(&socket, room, room_state).await);
+        },
+    );
+
     s.on(
         ClientEvent::EndVote,
         |socket: SocketRef,
@@ -144,12 +220,32 @@ pub async fn on_connection(
         },
     );
 
-    s.on_disconnect(|s: SocketRef, State(Sessions(sessions))| async move {
-        handle_disconnect(s, sessions).await;
+    s.on(
+        ClientEvent::Nudge,
+        |socket: SocketRef,
+         Data::<String>(room),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::Nudge, room, "Received event");
+            ack_result(ack_sender, votes::handle_nudge(&socket, room, room_state).await);
+        },
+    );
+
+    // Games register the handlers of their own events
+    for game in GAMES {
+        game.register_events(&s);
+    }
+
+    s.on_disconnect(|s: SocketRef,
+                      State(Sessions(sessions)),
+                      room_state: State<RoomState>,
+                      State(Users(users_state))| async move {
+        handle_disconnect(s, sessions, &room_state, users_state).await;
     });
 }
 
-async fn handle_disconnect(s: SocketRef, sessions: &RwLock<HashMap<Uuid, Session>>) {
+async fn handle_disconnect(s: SocketRef, sessions: &RwLock<HashMap<Uuid, Session>>,
+                           room_state: &RoomState, users_state: &RwLock<HashMap<String, User>>) {
     let mut session = s.extensions.get::<Session>().unwrap().clone();
     session.connected = false;
 
@@ -160,7 +256,10 @@ async fn handle_disconnect(s: SocketRef, sessions: &RwLock<HashMap<Uuid, Session
         .unwrap()
         .connected = false;
 
-    s.broadcast().emit("user disconnected", session).ok();
+    let user_id = session.user_id.clone();
+    s.extensions.insert(session);
+    let _ = s.leave_all();
+    rooms::broadcast_presence(&s, &user_id, room_state, users_state).await;
 }
 
 fn emit_reply(socket_ref: &SocketRef, server_event: ServerEvent) {
@@ -171,13 +270,27 @@ fn emit_reply(socket_ref: &SocketRef, server_event: ServerEvent) {
 }
 
 fn emit_within(socket_ref: &SocketRef, rooms: impl RoomParam, server_event: ServerEvent) {
+    emit_to(socket_ref.within(rooms), server_event);
+}
+
+/// Emits to the rooms selected by operators, which can be taken from a socket ahead of time to
+/// emit from a task that outlives its handler
+fn emit_to(operators: Operators, server_event: ServerEvent) {
     let event_id = server_event.event_id();
-    if let Err(error) = socket_ref.within(rooms).emit(event_id, server_event) {
+    if let Err(error) = operators.emit(event_id, server_event) {
         error!(error = debug(error), event_id, "failed to emit to rooms")
     }
 }
 
-fn ack_result<'a, M: AsRef<str>, T: Serialize>(ack_sender: AckSender, result: Result<T, M>) {
+/// Runs the task once the deadline has passed, or right away if it already has
+fn schedule_at(deadline: DateTime<Utc>, task: impl Future<Output = ()> + Send + 'static) {
+    tokio::spawn(async move {
+        tokio::time::sleep((deadline - Utc::now()).to_std().unwrap_or_default()).await;
+        task.await;
+    });
+}
+
+fn ack_result<M: AsRef<str>, T: Serialize>(ack_sender: AckSender, result: Result<T, M>) {
     let ack_result = match result {
         Ok(content) => {
             debug!("Sending ack OK");
@@ -199,7 +312,6 @@ enum ConnectError {
     InvalidUsername,
     EncodeError(serde_json::Error),
     SocketError(socketioxide::SendError),
-    BroadcastError(socketioxide::BroadcastError),
 }
 
 /// Handles the connection of a new user
@@ -260,11 +372,6 @@ async fn session_connect(
 
     _ = s.emit("user", &user_dto);
     emit_reply(s, ServerEvent::User(&user_dto));
-    let res = UserConnectedRes::new(&session, user_dto);
-
-    s.broadcast()
-        .emit("user_connected", res)
-        .map_err(ConnectError::BroadcastError)?;
     Ok(())
 }
 
@@ -273,15 +380,43 @@ fn log_emit_err<E: Debug>(error: E) {
 }
 
 use crate::dto::AckResult;
-use crate::state::game::Game;
 use sha2::{Digest, Sha256};
-use socketioxide::operators::RoomParam;
+use socketioxide::operators::{Operators, RoomParam};
 use tokio::sync::RwLock;
 
 mod rounds;
 mod votes;
 mod rooms;
 mod users;
+pub(crate) mod retro;
+pub(crate) mod lean_coffee;
+pub(crate) mod stories;
+pub(crate) mod sizing;
+
+/// Checks that the socket's user is a facilitator of the room, returning their user ID
+async fn require_facilitator(socket: &SocketRef, room_state: &RoomState, room: &str) -> Result<String, String> {
+    let user_id = socket.extensions.get::<Session>().unwrap().user_id.clone();
+    let rooms = room_state.rooms.read().await;
+    let Some(room_info) = rooms.get(room) else {
+        return Err(format!("room with ID \"{room}\" could not be found"));
+    };
+    if !room_info.is_facilitator(&user_id) {
+        return Err("only facilitators of the room can do that".into());
+    }
+    Ok(user_id)
+}
+
+/// Checks that the room's game handles the event, returning the room
+async fn require_event(room_state: &RoomState, room: &str, event: ClientEvent) -> Result<Room, String> {
+    let rooms = room_state.rooms.read().await;
+    let Some(room_info) = rooms.get(room) else {
+        return Err(format!("room with ID \"{room}\" could not be found"));
+    };
+    if !room_info.game.accepts(event) {
+        return Err(format!("\"{event}\" is not available in {} rooms", room_info.game.name()));
+    }
+    Ok(room_info.clone())
+}
 
 fn hash_email(email: &str) -> String {
     let hash = Sha256::digest(email);
@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+use socketioxide::extract::{AckSender, Data, SocketRef, State};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+use crate::event::{ClientEvent, ServerEvent, StartTopicIn, TimeboxIn, TopicIn, TopicRefIn};
+use crate::handlers;
+use crate::handlers::{rounds, votes, EventResult};
+use crate::id::encode_id;
+use crate::state::{RoomState, Session};
+use crate::state::lean_coffee::{
+    LeanCoffee, LeanCoffeeError, Topic, TopicDTO, TopicStatus, DEFAULT_EXTENSION_MINUTES, DEFAULT_TIMEBOX_MINUTES,
+};
+use crate::state::round::RoundOpts;
+use crate::state::round_type::RoundType;
+
+/// Registers the handlers of the Lean Coffee topic events on a newly connected socket
+pub(crate) fn register(s: &SocketRef) {
+    s.on(
+        ClientEvent::ProposeTopic,
+        |socket: SocketRef,
+         Data(TopicIn { room, title }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::ProposeTopic, room, "Received event");
+            handlers::ack_result(ack_sender, handle_propose_topic(&socket, room, title, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::DotTopic,
+        |socket: SocketRef,
+         Data(TopicRefIn { room, topic_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::DotTopic, room, topic_id, "Received event");
+            handlers::ack_result(ack_sender, handle_dot_topic(&socket, room, topic_id, false, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::UndotTopic,
+        |socket: SocketRef,
+         Data(TopicRefIn { room, topic_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::UndotTopic, room, topic_id, "Received event");
+            handlers::ack_result(ack_sender, handle_dot_topic(&socket, room, topic_id, true, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::StartTopic,
+        |socket: SocketRef,
+         Data(StartTopicIn { room, topic_id, minutes }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::StartTopic, room, topic_id, minutes, "Received event");
+            handlers::ack_result(ack_sender, handle_start_topic(&socket, room, topic_id, minutes, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::ExtendTopic,
+        |socket: SocketRef,
+         Data(TimeboxIn { room, minutes }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::ExtendTopic, room, minutes, "Received event");
+            handlers::ack_result(ack_sender, handle_extend_topic(&socket, room, minutes, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::FinishTopic,
+        |socket: SocketRef,
+         Data::<String>(room),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::FinishTopic, room, "Received event");
+            handlers::ack_result(ack_sender, handle_finish_topic(&socket, room, room_state).await);
+        },
+    );
+}
+
+pub async fn handle_propose_topic(s: &SocketRef, room: String, title: String,
+                                  room_state: State<RoomState>) -> Result<TopicDTO, String> {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    handlers::require_event(&room_state, &room, ClientEvent::ProposeTopic).await?;
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("topics cannot be empty".into());
+    }
+
+    let topic = Topic {
+        topic_id: encode_id(&Uuid::new_v4()),
+        author: user_id,
+        title: title.to_owned(),
+        created: Utc::now(),
+        dots: vec![],
+        status: TopicStatus::Queued,
+    };
+    room_state.lean_coffee.write().await.entry(room.clone()).or_default().topics.push(topic.clone());
+
+    emit_lean_coffee(s, &room, &room_state).await;
+    Ok(topic.into())
+}
+
+pub async fn handle_dot_topic(s: &SocketRef, room: String, topic_id: String, remove: bool,
+                              room_state: State<RoomState>) -> EventResult {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    let event = if remove { ClientEvent::UndotTopic } else { ClientEvent::DotTopic };
+    update_topics(&room_state, &room, event, |lean_coffee| {
+        if remove {
+            lean_coffee.remove_dot(&user_id, &topic_id)
+        } else {
+            lean_coffee.add_dot(&user_id, &topic_id)
+        }
+    }).await?;
+
+    emit_lean_coffee(s, &room, &room_state).await;
+    Ok(())
+}
+
+/// Closes the topic being discussed and starts a timebox for the next one, which opens a thumbs
+/// round for deciding whether to keep going once it runs out
+pub async fn handle_start_topic(s: &SocketRef, room: String, topic_id: Option<String>, minutes: Option<u8>,
+                                room_state: State<RoomState>) -> EventResult {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    let minutes = minutes.unwrap_or(DEFAULT_TIMEBOX_MINUTES);
+    let (topic_id, title) = update_topics(&room_state, &room, ClientEvent::StartTopic, |lean_coffee| {
+        let topic = lean_coffee.start(topic_id.as_deref(), minutes, Utc::now())?;
+        Ok((topic.topic_id.clone(), topic.title.clone()))
+    }).await?;
+
+    start_timebox(s, room, topic_id, title, room_state).await;
+    Ok(())
+}
+
+/// Gives the topic being discussed another timebox, after the room voted to keep going
+pub async fn handle_extend_topic(s: &SocketRef, room: String, minutes: Option<u8>,
+                                 room_state: State<RoomState>) -> EventResult {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    let minutes = minutes.unwrap_or(DEFAULT_EXTENSION_MINUTES);
+    let (topic_id, title) = update_topics(&room_state, &room, ClientEvent::ExtendTopic, |lean_coffee| {
+        let topic = lean_coffee.extend(minutes, Utc::now())?;
+        Ok((topic.topic_id.clone(), topic.title.clone()))
+    }).await?;
+
+    start_timebox(s, room, topic_id, title, room_state).await;
+    Ok(())
+}
+
+pub async fn handle_finish_topic(s: &SocketRef, room: String, room_state: State<RoomState>) -> EventResult {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    update_topics(&room_state, &room, ClientEvent::FinishTopic, LeanCoffee::finish).await?;
+
+    emit_lean_coffee(s, &room, &room_state).await;
+    Ok(())
+}
+
+/// Sends the room the timebox that was just started and, once its time is up, marks it as run out
+/// and opens a thumbs round named after its topic for the room to vote on whether to keep going
+async fn start_timebox(s: &SocketRef, room: String, topic_id: String, title: String, room_state: State<RoomState>) {
+    emit_lean_coffee(s, &room, &room_state).await;
+
+    let ends = room_state
+        .lean_coffee
+        .read()
+        .await
+        .get(&room)
+        .and_then(|l| l.discussion.as_ref())
+        .map(|d| d.ends);
+    if let Some(ends) = ends {
+        schedule_expiry(s, room, topic_id, title, ends, room_state.0);
+    }
+}
+
+/// Opens a thumbs round on whether to keep going once the topic's timebox runs out, unless the
+/// discussion was extended or moved on since
+fn schedule_expiry(s: &SocketRef, room: String, topic_id: String, title: String, ends: DateTime<Utc>,
+                   room_state: &'static RoomState) {
+    let rounds_operators = s.within(room.clone());
+    let votes_operators = s.within(room.clone());
+    let round_operators = s.within(room.clone());
+    let topics_operators = s.within(room.clone());
+    handlers::schedule_at(ends, async move {
+        let update = {
+            let mut lean_coffee = room_state.lean_coffee.write().await;
+            let Some(lean_coffee) = lean_coffee.get_mut(&room) else {
+                return;
+            };
+            if !lean_coffee.expire(&topic_id, ends) {
+                return;
+            }
+            lean_coffee.view(&room)
+        };
+
+        // A round still running is revealed, so its votes go into the history with a result
+        if !room_state.round_done(&room).await {
+            if let Err(error) = votes::reveal_round(room_state, &room).await {
+                error!(error, room, "failed to reveal the round before the thumbs vote");
+                return;
+            }
+        }
+
+        let round_opts = RoundOpts {
+            candidates: vec![],
+            max_votes: 1,
+            anonymous: false,
+            round_type: RoundType::Thumbs.to_string(),
+            poll: None,
+        };
+        let (rounds, current_round) = rounds::open_round(&room, Some(title), RoundType::Thumbs, round_opts, room_state).await;
+        handlers::emit_to(rounds_operators, ServerEvent::Rounds(&rounds));
+        handlers::emit_to(votes_operators, ServerEvent::Votes(&vec![]));
+        handlers::emit_to(round_operators, ServerEvent::CurrentRound(&current_round));
+        handlers::emit_to(topics_operators, ServerEvent::LeanCoffee(&update));
+    });
+}
+
+/// Applies a change to the room's topics, turning Lean Coffee errors into event errors
+async fn update_topics<T>(room_state: &RoomState, room: &str, event: ClientEvent,
+                          update: impl FnOnce(&mut LeanCoffee) -> Result<T, LeanCoffeeError>) -> Result<T, String> {
+    handlers::require_event(room_state, room, event).await?;
+    let mut lean_coffee = room_state.lean_coffee.write().await;
+    update(lean_coffee.entry(room.to_owned()).or_default()).map_err(|e| e.to_string())
+}
+
+/// Sends a member joining the room its Lean Coffee topics
+pub(crate) async fn send_lean_coffee(socket: &SocketRef, room: &str, room_state: &RoomState) {
+    let lean_coffee = room_state.lean_coffee.read().await.get(room).cloned().unwrap_or_default().view(room);
+    debug!(count = lean_coffee.queue.len(), "Sending Lean Coffee topics...");
+    handlers::emit_reply(socket, ServerEvent::LeanCoffee(&lean_coffee));
+}
+
+pub(crate) async fn emit_lean_coffee(s: &SocketRef, room: &str, room_state: &RoomState) {
+    let update = room_state.lean_coffee.read().await.get(room).cloned().unwrap_or_default().view(room);
+    handlers::emit_within(s, room.to_owned(), ServerEvent::LeanCoffee(&update));
+}
@@ -0,0 +1,635 @@
+use std::collections::HashMap;
+use chrono::{NaiveDate, Utc};
+use socketioxide::extract::{AckSender, Data, SocketRef, State};
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+use uuid::Uuid;
+use crate::event::{
+    ActionIn, ActionRefIn, AssignActionIn, CardEditIn, CardIn, CardRefIn, ClientEvent, DotIn, DotVoteIn, GroupCardsIn,
+    MergeCardsIn, MoveCardIn, PhaseIn, RenameGroupIn, ServerEvent,
+};
+use crate::handlers;
+use crate::handlers::EventResult;
+use crate::id::encode_id;
+use crate::state::{Room, RoomState, Session, User, Users};
+use crate::state::user::UserDTO;
+use crate::state::retro::{
+    ActionItem, ActionItemDTO, ActionStatus, BoardError, Card, CardDTO, CardGroup, RetroBoard, RoomActionsDTO,
+    RetroPhase, RoomBoardDTO, RoomPhaseDTO,
+};
+
+/// Phases in which action items can be created and assigned
+const ACTION_PHASES: &[RetroPhase] = &[RetroPhase::Discuss, RetroPhase::ActionItems];
+
+/// Registers the handlers of the retro board, phase and action item events on a newly connected socket
+pub(crate) fn register(s: &SocketRef) {
+    s.on(
+        ClientEvent::AddCard,
+        |socket: SocketRef,
+         Data(CardIn { room, column, text }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::AddCard, room, column, "Received event");
+            handlers::ack_result(ack_sender, handle_add_card(&socket, room, column, text, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::EditCard,
+        |socket: SocketRef,
+         Data(CardEditIn { room, card_id, column, text }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::EditCard, room, card_id, "Received event");
+            handlers::ack_result(ack_sender, handle_edit_card(&socket, room, card_id, column, text, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::DeleteCard,
+        |socket: SocketRef,
+         Data(CardRefIn { room, card_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::DeleteCard, room, card_id, "Received event");
+            handlers::ack_result(ack_sender, handle_delete_card(&socket, room, card_id, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::GroupCards,
+        |socket: SocketRef,
+         Data(GroupCardsIn { room, title, card_ids }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::GroupCards, room, title, "Received event");
+            handlers::ack_result(ack_sender, handle_group_cards(&socket, room, title, card_ids, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::MoveCard,
+        |socket: SocketRef,
+         Data(MoveCardIn { room, card_id, group_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::MoveCard, room, card_id, group_id, "Received event");
+            handlers::ack_result(ack_sender, handle_move_card(&socket, room, card_id, group_id, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::RenameGroup,
+        |socket: SocketRef,
+         Data(RenameGroupIn { room, group_id, title }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::RenameGroup, room, group_id, title, "Received event");
+            handlers::ack_result(ack_sender, handle_rename_group(&socket, room, group_id, title, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::MergeCards,
+        |socket: SocketRef,
+         Data(MergeCardsIn { room, card_id, duplicate_ids }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::MergeCards, room, card_id, "Received event");
+            handlers::ack_result(ack_sender, handle_merge_cards(&socket, room, card_id, duplicate_ids, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::StartDotVote,
+        |socket: SocketRef,
+         Data(DotVoteIn { room, dots }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::StartDotVote, room, dots, "Received event");
+            handlers::ack_result(ack_sender, handle_start_dot_vote(&socket, room, dots, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::AddDot,
+        |socket: SocketRef,
+         Data(DotIn { room, item_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::AddDot, room, item_id, "Received event");
+            handlers::ack_result(ack_sender, handle_dot(&socket, room, item_id, false, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::RemoveDot,
+        |socket: SocketRef,
+         Data(DotIn { room, item_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::RemoveDot, room, item_id, "Received event");
+            handlers::ack_result(ack_sender, handle_dot(&socket, room, item_id, true, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::EndDotVote,
+        |socket: SocketRef,
+         Data::<String>(room),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::EndDotVote, room, "Received event");
+            handlers::ack_result(ack_sender, handle_end_dot_vote(&socket, room, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::CreateAction,
+        |socket: SocketRef,
+         Data(ActionIn { room, text, owner_id, due }),
+         room_state: State<RoomState>,
+         State(Users(users_state)),
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::CreateAction, room, owner_id, due, "Received event");
+            handlers::ack_result(ack_sender, handle_create_action(&socket, room, text, owner_id, due, room_state, users_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::AssignAction,
+        |socket: SocketRef,
+         Data(AssignActionIn { room, action_id, owner_id }),
+         room_state: State<RoomState>,
+         State(Users(users_state)),
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::AssignAction, room, action_id, owner_id, "Received event");
+            handlers::ack_result(ack_sender, handle_assign_action(&socket, room, action_id, owner_id, room_state, users_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::CompleteAction,
+        |socket: SocketRef,
+         Data(ActionRefIn { room, action_id }),
+         room_state: State<RoomState>,
+         State(Users(users_state)),
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::CompleteAction, room, action_id, "Received event");
+            handlers::ack_result(ack_sender, handle_set_action_status(&socket, room, action_id, ActionStatus::Done, room_state, users_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::ReopenAction,
+        |socket: SocketRef,
+         Data(ActionRefIn { room, action_id }),
+         room_state: State<RoomState>,
+         State(Users(users_state)),
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::ReopenAction, room, action_id, "Received event");
+            handlers::ack_result(ack_sender, handle_set_action_status(&socket, room, action_id, ActionStatus::Open, room_state, users_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::SetPhase,
+        |socket: SocketRef,
+         Data(PhaseIn { room, phase }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::SetPhase, room, %phase, "Received event");
+            handlers::ack_result(ack_sender, handle_set_phase(&socket, room, phase, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::Reveal,
+        |socket: SocketRef,
+         Data::<String>(room),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::Reveal, room, "Received event");
+            handlers::ack_result(ack_sender, handle_reveal(&socket, room, room_state).await);
+        },
+    );
+}
+
+pub async fn handle_add_card(s: &SocketRef, room: String, column: String, text: String,
+                             room_state: State<RoomState>) -> Result<CardDTO, String> {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    let room_info = require_phase(&room_state, &room, ClientEvent::AddCard, &[RetroPhase::Brainstorm]).await?;
+    let text = validate_card(&room_info, &column, text)?;
+
+    let card = Card {
+        card_id: encode_id(&Uuid::new_v4()),
+        author: user_id,
+        column,
+        text,
+        created: Utc::now(),
+        group_id: None,
+        merged: vec![],
+    };
+    room_state
+        .boards
+        .write()
+        .await
+        .entry(room.clone())
+        .or_default()
+        .cards
+        .push(card.clone());
+
+    emit_board(s, &room, &room_state).await;
+    Ok(card.into())
+}
+
+pub async fn handle_edit_card(s: &SocketRef, room: String, card_id: String, column: Option<String>,
+                              text: String, room_state: State<RoomState>) -> EventResult {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    let room_info = require_phase(&room_state, &room, ClientEvent::EditCard, &[RetroPhase::Brainstorm]).await?;
+
+    {
+        let mut boards = room_state.boards.write().await;
+        let Some(card) = boards.get_mut(&room).and_then(|b| b.card_mut(&card_id)) else {
+            return Err(format!("card with ID \"{card_id}\" could not be found"));
+        };
+        if card.author != user_id {
+            return Err("only the author of a card can edit it".into());
+        }
+        let column = column.unwrap_or_else(|| card.column.clone());
+        card.text = validate_card(&room_info, &column, text)?;
+        card.column = column;
+    }
+
+    emit_board(s, &room, &room_state).await;
+    Ok(())
+}
+
+pub async fn handle_delete_card(s: &SocketRef, room: String, card_id: String,
+                                room_state: State<RoomState>) -> EventResult {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    let room_info = require_phase(&room_state, &room, ClientEvent::DeleteCard, &[RetroPhase::Brainstorm]).await?;
+    let is_facilitator = room_info.is_facilitator(&user_id);
+
+    {
+        let mut boards = room_state.boards.write().await;
+        let Some(board) = boards.get_mut(&room) else {
+            return Err(format!("card with ID \"{card_id}\" could not be found"));
+        };
+        let Some(index) = board.cards.iter().position(|c| c.card_id == card_id) else {
+            return Err(format!("card with ID \"{card_id}\" could not be found"));
+        };
+        if board.cards[index].author != user_id && !is_facilitator {
+            return Err("only the author of a card or a facilitator can delete it".into());
+        }
+        board.cards.remove(index);
+        board.prune_groups();
+    }
+
+    emit_board(s, &room, &room_state).await;
+    Ok(())
+}
+
+/// Shows every card on the board to all members of the room
+pub async fn handle_reveal(s: &SocketRef, room: String, room_state: State<RoomState>) -> EventResult {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    require_phase(&room_state, &room, ClientEvent::Reveal, &[RetroPhase::Brainstorm, RetroPhase::Group]).await?;
+
+    room_state.boards.write().await.entry(room.clone()).or_default().revealed = true;
+
+    emit_board(s, &room, &room_state).await;
+    Ok(())
+}
+
+pub async fn handle_group_cards(s: &SocketRef, room: String, title: String, card_ids: Vec<String>,
+                                room_state: State<RoomState>) -> Result<CardGroup, String> {
+    let title = validate_title(title)?;
+    let group = update_board(&room_state, &room, ClientEvent::GroupCards, &[RetroPhase::Group], |board| {
+        board.group_cards(encode_id(&Uuid::new_v4()), title, &card_ids)
+    }).await?;
+
+    emit_board(s, &room, &room_state).await;
+    Ok(group)
+}
+
+pub async fn handle_move_card(s: &SocketRef, room: String, card_id: String, group_id: Option<String>,
+                              room_state: State<RoomState>) -> EventResult {
+    update_board(&room_state, &room, ClientEvent::MoveCard, &[RetroPhase::Group], |board| board.move_card(&card_id, group_id)).await?;
+
+    emit_board(s, &room, &room_state).await;
+    Ok(())
+}
+
+pub async fn handle_rename_group(s: &SocketRef, room: String, group_id: String, title: String,
+                                 room_state: State<RoomState>) -> EventResult {
+    let title = validate_title(title)?;
+    update_board(&room_state, &room, ClientEvent::RenameGroup, &[RetroPhase::Group], |board| board.rename_group(&group_id, title)).await?;
+
+    emit_board(s, &room, &room_state).await;
+    Ok(())
+}
+
+pub async fn handle_merge_cards(s: &SocketRef, room: String, card_id: String, duplicate_ids: Vec<String>,
+                                room_state: State<RoomState>) -> EventResult {
+    update_board(&room_state, &room, ClientEvent::MergeCards, &[RetroPhase::Group], |board| board.merge_cards(&card_id, &duplicate_ids)).await?;
+
+    emit_board(s, &room, &room_state).await;
+    Ok(())
+}
+
+pub async fn handle_start_dot_vote(s: &SocketRef, room: String, dots_per_member: u8,
+                                   room_state: State<RoomState>) -> EventResult {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    if dots_per_member == 0 {
+        return Err("members need at least one dot".into());
+    }
+    update_board(&room_state, &room, ClientEvent::StartDotVote, &[RetroPhase::Vote], |board| board.start_dot_vote(dots_per_member)).await?;
+
+    emit_board(s, &room, &room_state).await;
+    Ok(())
+}
+
+pub async fn handle_dot(s: &SocketRef, room: String, item_id: String, remove: bool,
+                        room_state: State<RoomState>) -> EventResult {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    let event = if remove { ClientEvent::RemoveDot } else { ClientEvent::AddDot };
+    update_board(&room_state, &room, event, &[RetroPhase::Vote], |board| {
+        if remove {
+            board.remove_dot(&user_id, &item_id)
+        } else {
+            board.add_dot(&user_id, &item_id)
+        }
+    }).await?;
+
+    emit_board(s, &room, &room_state).await;
+    Ok(())
+}
+
+pub async fn handle_end_dot_vote(s: &SocketRef, room: String, room_state: State<RoomState>) -> EventResult {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    update_board(&room_state, &room, ClientEvent::EndDotVote, &[RetroPhase::Vote], RetroBoard::end_dot_vote).await?;
+
+    emit_board(s, &room, &room_state).await;
+    Ok(())
+}
+
+/// Moves the retro to another phase, revealing the cards and closing the dot vote once their phases are over
+pub async fn handle_set_phase(s: &SocketRef, room: String, phase: RetroPhase,
+                              room_state: State<RoomState>) -> EventResult {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    handlers::require_event(&room_state, &room, ClientEvent::SetPhase).await?;
+
+    {
+        let mut phases = room_state.phases.write().await;
+        let current = phases.entry(room.clone()).or_default();
+        *current = current.transition(phase).map_err(|e| e.to_string())?;
+    }
+
+    let board_changed = {
+        let mut boards = room_state.boards.write().await;
+        let board = boards.entry(room.clone()).or_default();
+        let reveal = phase.reveals_cards() && !board.revealed;
+        let end_vote = phase.ends_dot_vote() && board.dot_vote.as_ref().is_some_and(|v| v.open);
+        if reveal {
+            board.revealed = true;
+        }
+        if end_vote {
+            board.end_dot_vote().map_err(|e| e.to_string())?;
+        }
+        reveal || end_vote
+    };
+
+    emit_phase(s, &room, &room_state).await;
+    if board_changed {
+        emit_board(s, &room, &room_state).await;
+    }
+    Ok(())
+}
+
+pub async fn handle_create_action(s: &SocketRef, room: String, text: String, owner: Option<String>,
+                                  due: Option<String>, room_state: State<RoomState>,
+                                  users_state: &RwLock<HashMap<String, User>>) -> Result<ActionItemDTO, String> {
+    require_phase(&room_state, &room, ClientEvent::CreateAction, ACTION_PHASES).await?;
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("action items cannot be empty".into());
+    }
+    if let Some(owner) = &owner {
+        require_member(&room_state, &room, owner).await?;
+    }
+    let due = due
+        .map(|due| NaiveDate::parse_from_str(&due, "%Y-%m-%d"))
+        .transpose()
+        .map_err(|e| format!("invalid due date: {e}"))?;
+
+    let action = ActionItem {
+        action_id: encode_id(&Uuid::new_v4()),
+        text: text.to_owned(),
+        owner,
+        due,
+        status: ActionStatus::Open,
+        created: Utc::now(),
+    };
+    room_state.actions.write().await.entry(room.clone()).or_default().push(action.clone());
+
+    emit_actions(s, &room, &room_state, users_state).await;
+    let owner = owner_dto(&action, users_state).await;
+    Ok(action.into_dto(owner))
+}
+
+pub async fn handle_assign_action(s: &SocketRef, room: String, action_id: String, owner: Option<String>,
+                                  room_state: State<RoomState>,
+                                  users_state: &RwLock<HashMap<String, User>>) -> EventResult {
+    let room_info = require_phase(&room_state, &room, ClientEvent::AssignAction, ACTION_PHASES).await?;
+    if let Some(owner) = &owner {
+        require_member(&room_state, &room, owner).await?;
+    }
+    update_action(s, &room_info, &room_state, &action_id, |action| action.owner = owner).await?;
+
+    emit_actions(s, &room, &room_state, users_state).await;
+    Ok(())
+}
+
+/// Completes or reopens an action item
+pub async fn handle_set_action_status(s: &SocketRef, room: String, action_id: String, status: ActionStatus,
+                                      room_state: State<RoomState>,
+                                      users_state: &RwLock<HashMap<String, User>>) -> EventResult {
+    // Action items carried over from the last retro are followed up during check-in
+    let event = match status {
+        ActionStatus::Done => ClientEvent::CompleteAction,
+        ActionStatus::Open => ClientEvent::ReopenAction,
+    };
+    let room_info = require_phase(&room_state, &room, event, &[RetroPhase::CheckIn, RetroPhase::Discuss, RetroPhase::ActionItems]).await?;
+    update_action(s, &room_info, &room_state, &action_id, |action| action.status = status).await?;
+
+    emit_actions(s, &room, &room_state, users_state).await;
+    Ok(())
+}
+
+/// Changes an action item on behalf of its owner or a facilitator of the room
+async fn update_action(s: &SocketRef, room_info: &Room, room_state: &RoomState, action_id: &str,
+                       update: impl FnOnce(&mut ActionItem)) -> EventResult {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    let mut actions = room_state.actions.write().await;
+    let Some(action) = actions
+        .get_mut(&room_info.room_id)
+        .and_then(|a| a.iter_mut().find(|a| a.action_id == action_id)) else {
+        return Err(format!("action item with ID \"{action_id}\" could not be found"));
+    };
+    if action.owner.as_ref() != Some(&user_id) && !room_info.is_facilitator(&user_id) {
+        return Err("only the owner of an action item or a facilitator can change it".into());
+    }
+    update(action);
+    Ok(())
+}
+
+/// Lists the action items of a room, optionally only the ones that are still open
+pub(crate) async fn action_list(room: &str, only_open: bool, room_state: &RoomState,
+                                users_state: &RwLock<HashMap<String, User>>) -> Vec<ActionItemDTO> {
+    let actions: Vec<ActionItem> = room_state
+        .actions
+        .read()
+        .await
+        .get(room)
+        .into_iter()
+        .flatten()
+        .filter(|a| !only_open || a.status == ActionStatus::Open)
+        .cloned()
+        .collect();
+    let mut dtos = Vec::with_capacity(actions.len());
+    for action in actions {
+        let owner = owner_dto(&action, users_state).await;
+        dtos.push(action.into_dto(owner));
+    }
+    dtos
+}
+
+async fn emit_actions(s: &SocketRef, room: &str, room_state: &RoomState,
+                      users_state: &RwLock<HashMap<String, User>>) {
+    let actions = RoomActionsDTO {
+        room: room.to_owned(),
+        actions: action_list(room, false, room_state, users_state).await,
+    };
+    handlers::emit_within(s, room.to_owned(), ServerEvent::Actions(&actions));
+}
+
+async fn owner_dto(action: &ActionItem, users_state: &RwLock<HashMap<String, User>>) -> Option<UserDTO> {
+    let owner = action.owner.as_ref()?;
+    let mut dto: UserDTO = users_state.read().await.get(owner)?.clone().into();
+    dto.email = "".to_string();
+    Some(dto)
+}
+
+async fn require_member(room_state: &RoomState, room: &str, user_id: &str) -> EventResult {
+    let is_member = room_state
+        .members
+        .read()
+        .await
+        .get(room)
+        .is_some_and(|m| m.contains_key(user_id));
+    if !is_member {
+        return Err(format!("user \"{user_id}\" is not a member of this room"));
+    }
+    Ok(())
+}
+
+/// Applies a change to the room's board during one of the given phases, turning board errors into event errors
+async fn update_board<T>(room_state: &RoomState, room: &str, event: ClientEvent, phases: &[RetroPhase],
+                         update: impl FnOnce(&mut RetroBoard) -> Result<T, BoardError>) -> Result<T, String> {
+    require_phase(room_state, room, event, phases).await?;
+    let mut boards = room_state.boards.write().await;
+    let Some(board) = boards.get_mut(room) else {
+        return Err("the room has no retro board".into());
+    };
+    update(board).map_err(|e| e.to_string())
+}
+
+/// Sends a member joining the room its retro board, phase and action items
+pub(crate) async fn send_retro(socket: &SocketRef, room: &str, room_state: &RoomState,
+                               users_state: &RwLock<HashMap<String, User>>) {
+    let user_id = socket.extensions.get::<Session>().unwrap().user_id.clone();
+    if let Some(board) = room_state.boards.read().await.get(room) {
+        let board = RoomBoardDTO { room: room.to_owned(), board: board.view_for(&user_id) };
+        debug!(count = board.board.cards.len(), "Sending retro board...");
+        handlers::emit_reply(socket, ServerEvent::Board(&board));
+    }
+
+    let phase = RoomPhaseDTO { room: room.to_owned(), phase: room_state.get_phase(room).await };
+    debug!(phase = %phase.phase, "Sending retro phase...");
+    handlers::emit_reply(socket, ServerEvent::Phase(&phase));
+
+    let actions = RoomActionsDTO {
+        room: room.to_owned(),
+        actions: action_list(room, false, room_state, users_state).await,
+    };
+    debug!(count = actions.actions.len(), "Sending action items...");
+    handlers::emit_reply(socket, ServerEvent::Actions(&actions));
+}
+
+/// A new round in a retro room starts the next retro on an empty board, so bring up what is left
+/// from the last ones
+pub(crate) async fn start_retro(s: &SocketRef, room: &str, room_state: &RoomState,
+                                users_state: &RwLock<HashMap<String, User>>) {
+    let board = RoomBoardDTO { room: room.to_owned(), board: RetroBoard::default().view() };
+    handlers::emit_within(s, room.to_owned(), ServerEvent::Board(&board));
+
+    room_state.phases.write().await.insert(room.to_owned(), RetroPhase::default());
+    emit_phase(s, room, room_state).await;
+
+    let actions = action_list(room, true, room_state, users_state).await;
+    if !actions.is_empty() {
+        let actions = RoomActionsDTO { room: room.to_owned(), actions };
+        handlers::emit_within(s, room.to_owned(), ServerEvent::OpenActions(&actions));
+    }
+}
+
+/// Sends the board to the members of the room, each only seeing their own cards until it is revealed
+pub(crate) async fn emit_board(s: &SocketRef, room: &str, room_state: &RoomState) {
+    let members = room_state.get_members(room).await;
+    let boards = room_state.boards.read().await;
+    let default_board = RetroBoard::default();
+    let board = boards.get(room).unwrap_or(&default_board);
+
+    if !board.is_personal() {
+        let update = RoomBoardDTO { room: room.to_owned(), board: board.view() };
+        handlers::emit_within(s, room.to_owned(), ServerEvent::Board(&update));
+        return;
+    }
+    for member in members {
+        let update = RoomBoardDTO { room: room.to_owned(), board: board.view_for(&member.user_id) };
+        handlers::emit_within(s, member.user_id, ServerEvent::Board(&update));
+    }
+}
+
+pub(crate) async fn emit_phase(s: &SocketRef, room: &str, room_state: &RoomState) {
+    let phase = RoomPhaseDTO { room: room.to_owned(), phase: room_state.get_phase(room).await };
+    handlers::emit_within(s, room.to_owned(), ServerEvent::Phase(&phase));
+}
+
+/// Checks that the room's game handles the event and that its retro is in one of the allowed phases,
+/// returning the room
+pub(crate) async fn require_phase(room_state: &RoomState, room: &str, event: ClientEvent,
+                                  allowed: &[RetroPhase]) -> Result<Room, String> {
+    let room_info = handlers::require_event(room_state, room, event).await?;
+    room_state.get_phase(room).await.require(allowed).map_err(|e| e.to_string())?;
+    Ok(room_info)
+}
+
+fn validate_card(room: &Room, column: &str, text: String) -> Result<String, String> {
+    if !room.has_retro_column(column) {
+        return Err(format!("unknown retro column \"{column}\""));
+    }
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("cards cannot be empty".into());
+    }
+    Ok(text.to_owned())
+}
+
+fn validate_title(title: String) -> Result<String, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("group titles cannot be empty".into());
+    }
+    Ok(title.to_owned())
+}
@@ -1,24 +1,28 @@
 use socketioxide::extract::{SocketRef, State};
 use tokio::sync::RwLock;
-use std::collections::HashMap;
-use tracing::debug;
+use std::collections::{BTreeSet, HashMap};
+use tracing::{debug, error};
 use uuid::Uuid;
 use crate::{handlers, pokemon};
-use crate::event::ServerEvent;
+use crate::handlers::votes;
+use crate::event::{ServerEvent, TemplateIn};
 use crate::id::encode_id;
-use crate::state::{Room, RoomState, Session, User};
+use crate::state::{Member, Room, RoomState, Session, User};
 use crate::state::game::Game;
-use crate::state::room::RoomDTO;
+use crate::state::member::{MemberDTO, MemberRole, Presence};
+use crate::state::room::{parse_deck, DEFAULT_DECK, RoomDTO};
 use crate::state::round::CurrentRoundDTO;
-use crate::state::user::UserDTO;
+use crate::state::template::RetroTemplate;
 
-pub async fn handle_create(_: &SocketRef, room_name: String, game_name: String,
+pub async fn handle_create(socket: &SocketRef, room_name: String, game_name: String,
+                           template: Option<TemplateIn>,
                            room_state: State<RoomState>) -> Result<RoomDTO, String> {
+    let user_id = socket.extensions.get::<Session>().unwrap().user_id.clone();
     let room_id = encode_id(&Uuid::new_v4());
 
 
-    let game: Game = {
-        match (&game_name).parse() {
+    let game: &'static dyn Game = {
+        match game_name.parse() {
             Err(e) => {
                 return Err(format!("{e:?}"));
             }
@@ -26,10 +30,21 @@ pub async fn handle_create(_: &SocketRef, room_name: String, game_name: String,
         }
     };
 
+    // Only games with a board have columns to lay it out in
+    let template = match (game.template(), template) {
+        (default, None) => default,
+        (Some(_), Some(template)) => Some(resolve_template(template)?),
+        (None, Some(_)) => return Err(format!("templates cannot be used in {} rooms", game.name())),
+    };
+
     let room_info = Room {
         room_id,
         name: room_name,
         game,
+        facilitators: BTreeSet::from([user_id]),
+        banned: BTreeSet::new(),
+        template,
+        deck: DEFAULT_DECK.map(str::to_owned).to_vec(),
     };
 
 
@@ -38,41 +53,49 @@ pub async fn handle_create(_: &SocketRef, room_name: String, game_name: String,
     Ok(RoomDTO::from(room_info))
 }
 
-pub async fn handle_join(socket: &SocketRef, room_id: String, room_state: State<RoomState>,
+/// Looks up a built-in retro template by name or checks a user-defined one
+fn resolve_template(template: TemplateIn) -> Result<RetroTemplate, String> {
+    match template {
+        TemplateIn::Named(name) => name.parse().map_err(|e| format!("unknown retro template: {e:?}")),
+        TemplateIn::Custom(template) => {
+            template.validate().map_err(|e| e.to_string())?;
+            Ok(template)
+        }
+    }
+}
+
+pub async fn handle_join(socket: &SocketRef, room_id: String, role: Option<MemberRole>,
+                         room_state: State<RoomState>,
                          users_state: &RwLock<HashMap<String, User>>) -> Result<(), String> {
     let user_id = socket.extensions.get::<Session>().unwrap().user_id.clone();
 
-    let room_info: RoomDTO = {
+    let (room_info, game): (RoomDTO, &'static dyn Game) = {
         let rooms = room_state.rooms.write().await;
         let Some(room) = rooms.get(&room_id) else {
             return Err(format!("room with ID \"{room_id}\" could not be found"));
         };
-        room.clone().into()
+        if room.is_banned(&user_id) {
+            return Err("you have been banned from this room".into());
+        }
+        (room.clone().into(), room.game)
     };
 
-    let _ = socket.leave_all();
+    // Joins are additive, so a socket can follow several rooms at once until it leaves them
     let _ = socket.join(room_id.clone());
 
-    let members = {
+    {
         let mut members = room_state.members.write().await;
-        let room_members = members.entry(room_id.clone()).or_default();
-        room_members.insert(user_id.clone());
-        room_members.clone()
-    };
+        let member = members
+            .entry(room_id.clone())
+            .or_default()
+            .entry(user_id.clone())
+            .or_insert_with(|| Member::new(user_id.clone(), MemberRole::default()));
+        if let Some(role) = role {
+            member.role = role;
+        }
+    }
 
-    let users: Vec<UserDTO> = {
-        let users = users_state.read().await;
-        members
-            .iter()
-            .map(|id| {
-                users
-                    .get(id)
-                    .cloned()
-                    .unwrap_or_else(|| User::new(pokemon::random_name()))
-            })
-            .map(|u| u.into())
-            .collect()
-    };
+    let users = member_list(socket, &room_id, &room_state, users_state).await;
 
     let rounds: Vec<_> = room_state
         .get_rounds(&room_id)
@@ -95,22 +118,247 @@ pub async fn handle_join(socket: &SocketRef, room_id: String, room_state: State<
     }
 
     debug!(count = rounds.len(), "Sending rounds...");
-    handlers::emit_reply(&socket, ServerEvent::Rounds(&rounds));
+    handlers::emit_reply(socket, ServerEvent::Rounds(&rounds));
 
-    let votes: Vec<_> = room_state
-        .get_votes(&room_id)
-        .await
-        .into_iter()
-        .map(Into::into)
-        .collect();
+    let votes = room_state.get_vote_dtos(&room_id).await;
     debug!(count = votes.len(), "Sending votes...");
-    handlers::emit_reply(&socket, ServerEvent::Votes(&votes));
+    handlers::emit_reply(socket, ServerEvent::Votes(&votes));
+
+    game.on_join(socket, &room_id, &room_state, users_state).await;
 
     debug!(room_info = room_info.name, "Sending room info...");
-    handlers::emit_reply(&socket, ServerEvent::Room(&room_info));
+    handlers::emit_reply(socket, ServerEvent::Room(&room_info));
 
     debug!(count = users.len(), "Sending users...");
-    handlers::emit_within(&socket, room_id, ServerEvent::Users(&users));
+    handlers::emit_within(socket, room_id, ServerEvent::Users(&users));
+
+    Ok(())
+}
+
+pub async fn handle_set_role(socket: &SocketRef, room_id: String, role: MemberRole,
+                             room_state: State<RoomState>,
+                             users_state: &RwLock<HashMap<String, User>>) -> Result<(), String> {
+    let user_id = socket.extensions.get::<Session>().unwrap().user_id.clone();
+
+    let is_voter = {
+        let mut members = room_state.members.write().await;
+        let Some(member) = members.get_mut(&room_id).and_then(|m| m.get_mut(&user_id)) else {
+            return Err("you are not a member of this room".into());
+        };
+        member.role = role;
+        member.is_voter()
+    };
+
+    // Observers don't vote, so their vote in a round that is still running is dropped
+    let round_open = room_state.current_round.read().await.get(&room_id).is_some_and(|r| !r.flipped);
+    let vote_dropped = !is_voter && round_open && room_state
+        .votes
+        .write()
+        .await
+        .get_mut(&room_id)
+        .is_some_and(|votes| votes.remove(&user_id).is_some());
+
+    let users = member_list(socket, &room_id, &room_state, users_state).await;
+    handlers::emit_within(socket, room_id.clone(), ServerEvent::Users(&users));
+
+    if vote_dropped {
+        let votes = room_state.get_vote_dtos(&room_id).await;
+        handlers::emit_within(socket, room_id.clone(), ServerEvent::Votes(&votes));
+        votes::refresh_live_result(socket, &room_id, &room_state).await;
+    }
+
+    Ok(())
+}
+
+/// Builds the member list of a room, with participants listed before observers
+pub(crate) async fn member_list(socket: &SocketRef, room_id: &str, room_state: &RoomState,
+                                users_state: &RwLock<HashMap<String, User>>) -> Vec<MemberDTO> {
+    let mut members = room_state.get_members(room_id).await;
+    members.sort_by_key(|m| m.role != MemberRole::Participant);
+
+    let presence = member_presence(socket, room_id, &members);
+    let users = users_state.read().await;
+    members
+        .iter()
+        .map(|member| {
+            let user = users
+                .get(&member.user_id)
+                .cloned()
+                .unwrap_or_else(|| User::new(pokemon::random_name()));
+            let presence = presence.get(&member.user_id).copied().unwrap_or_default();
+            MemberDTO::new(user.into(), member, presence)
+        })
+        .collect()
+}
+
+/// Works out the presence in the room of each of its members, from the sockets in the room and
+/// in each member's own room
+fn member_presence(socket: &SocketRef, room_id: &str, members: &[Member]) -> HashMap<String, Presence> {
+    let connected_users = |sockets: Vec<SocketRef>| -> BTreeSet<String> {
+        sockets
+            .iter()
+            .filter_map(|s| s.extensions.get::<Session>().filter(|s| s.connected).map(|s| s.user_id.clone()))
+            .collect()
+    };
+    let online = connected_users(socket.within(room_id.to_owned()).sockets().unwrap_or_default());
+
+    members
+        .iter()
+        .map(|member| {
+            let presence = if online.contains(&member.user_id) {
+                Presence::Online
+            } else if connected_users(socket.within(member.user_id.clone()).sockets().unwrap_or_default()).is_empty() {
+                Presence::Away
+            } else {
+                Presence::Idle
+            };
+            (member.user_id.clone(), presence)
+        })
+        .collect()
+}
+
+/// Sends the updated member list to every room the user is a member of
+pub(crate) async fn broadcast_presence(socket: &SocketRef, user_id: &str, room_state: &RoomState,
+                                       users_state: &RwLock<HashMap<String, User>>) {
+    for room_id in room_state.get_member_rooms(user_id).await {
+        let users = member_list(socket, &room_id, room_state, users_state).await;
+        handlers::emit_within(socket, room_id, ServerEvent::Users(&users));
+    }
+}
+
+/// Makes another member a facilitator of the room, optionally stepping down as one
+pub async fn handle_add_facilitator(socket: &SocketRef, room_id: String, target_id: String,
+                                    step_down: bool, room_state: State<RoomState>) -> Result<(), String> {
+    let user_id = handlers::require_facilitator(socket, &room_state, &room_id).await?;
+
+    let is_member = room_state
+        .members
+        .read()
+        .await
+        .get(&room_id)
+        .is_some_and(|m| m.contains_key(&target_id));
+    if !is_member {
+        return Err(format!("user \"{target_id}\" is not a member of this room"));
+    }
+
+    let room_info: RoomDTO = {
+        let mut rooms = room_state.rooms.write().await;
+        let Some(room) = rooms.get_mut(&room_id) else {
+            return Err(format!("room with ID \"{room_id}\" could not be found"));
+        };
+        room.facilitators.insert(target_id.clone());
+        if step_down && target_id != user_id {
+            room.facilitators.remove(&user_id);
+        }
+        room.clone().into()
+    };
 
+    handlers::emit_within(socket, room_id, ServerEvent::Room(&room_info));
     Ok(())
 }
+
+/// Removes a member from the room and disconnects their sockets from it, optionally banning them
+pub async fn handle_remove_member(socket: &SocketRef, room_id: String, target_id: String, ban: bool,
+                                  room_state: State<RoomState>,
+                                  users_state: &RwLock<HashMap<String, User>>) -> Result<(), String> {
+    let user_id = handlers::require_facilitator(socket, &room_state, &room_id).await?;
+    if target_id == user_id {
+        return Err("you cannot remove yourself from the room".into());
+    }
+
+    let removed = remove_membership(socket, &room_id, &target_id, &room_state, users_state).await;
+    if !removed && !ban {
+        return Err(format!("user \"{target_id}\" is not a member of this room"));
+    }
+
+    let room_info: RoomDTO = {
+        let mut rooms = room_state.rooms.write().await;
+        let Some(room) = rooms.get_mut(&room_id) else {
+            return Err(format!("room with ID \"{room_id}\" could not be found"));
+        };
+        room.facilitators.remove(&target_id);
+        if ban {
+            room.banned.insert(target_id.clone());
+        }
+        room.clone().into()
+    };
+
+    handlers::emit_within(socket, target_id, ServerEvent::RemovedFromRoom(&room_id));
+    handlers::emit_within(socket, room_id, ServerEvent::Room(&room_info));
+
+    Ok(())
+}
+
+pub async fn handle_leave(socket: &SocketRef, room_id: String, room_state: State<RoomState>,
+                          users_state: &RwLock<HashMap<String, User>>) -> Result<(), String> {
+    let user_id = socket.extensions.get::<Session>().unwrap().user_id.clone();
+
+    if !remove_membership(socket, &room_id, &user_id, &room_state, users_state).await {
+        return Err("you are not a member of this room".into());
+    }
+
+    Ok(())
+}
+
+/// Sets the cards the room's stories and sizing board are estimated with, from the next story or
+/// sizing board on
+pub async fn handle_set_deck(socket: &SocketRef, room_id: String, deck: Vec<String>,
+                             room_state: State<RoomState>) -> Result<(), String> {
+    handlers::require_facilitator(socket, &room_state, &room_id).await?;
+    let deck = parse_deck(&deck).map_err(|e| e.to_string())?;
+
+    let room_info: RoomDTO = {
+        let mut rooms = room_state.rooms.write().await;
+        let Some(room) = rooms.get_mut(&room_id) else {
+            return Err(format!("room with ID \"{room_id}\" could not be found"));
+        };
+        room.deck = deck;
+        room.clone().into()
+    };
+
+    handlers::emit_within(socket, room_id, ServerEvent::Room(&room_info));
+    Ok(())
+}
+
+/// Removes a user from the room's members, drops their vote if the round hasn't been revealed yet
+/// and makes all of their sockets leave the room. The remaining members are sent the updated
+/// member list. Returns false if the user was not a member of the room.
+async fn remove_membership(socket: &SocketRef, room_id: &str, user_id: &str, room_state: &RoomState,
+                           users_state: &RwLock<HashMap<String, User>>) -> bool {
+    let removed = room_state
+        .members
+        .write()
+        .await
+        .get_mut(room_id)
+        .and_then(|m| m.remove(user_id));
+    if removed.is_none() {
+        return false;
+    }
+
+    let flipped = room_state
+        .current_round
+        .read()
+        .await
+        .get(room_id)
+        .is_some_and(|r| r.flipped);
+    let vote_removed = !flipped && room_state
+        .votes
+        .write()
+        .await
+        .get_mut(room_id)
+        .is_some_and(|votes| votes.remove(user_id).is_some());
+
+    if let Err(error) = socket.within(user_id.to_owned()).leave(room_id.to_owned()) {
+        error!(error = debug(error), room_id, "failed to remove sockets from room");
+    }
+
+    let users = member_list(socket, room_id, room_state, users_state).await;
+    handlers::emit_within(socket, room_id.to_owned(), ServerEvent::Users(&users));
+    if vote_removed {
+        let votes = room_state.get_vote_dtos(room_id).await;
+        handlers::emit_within(socket, room_id.to_owned(), ServerEvent::Votes(&votes));
+        votes::refresh_live_result(socket, room_id, room_state).await;
+    }
+
+    true
+}
@@ -1,44 +1,152 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
 use socketioxide::extract::{SocketRef, State};
-use crate::event::ServerEvent;
+use tokio::sync::RwLock;
+use tracing::error;
+use crate::event::{ClientEvent, ServerEvent};
 use crate::handlers;
-use crate::state::{RoomState, Round, Vote};
+use crate::handlers::votes;
+use crate::state::{RoomState, Round, Session, User, Vote};
+use crate::state::game::ExportDTO;
 use crate::state::round::{CurrentRound, CurrentRoundDTO, RoundDTO, RoundOpts};
+use crate::state::round_type::RoundType;
+use crate::state::vote::CANDIDATE_SEPARATOR;
 
-pub async fn handle_new(s: &SocketRef, room: String, round_opts: RoundOpts, room_state: State<RoomState>) -> Result<(), String> {
-    if !room_state
+pub async fn handle_new(s: &SocketRef, room: String, round_opts: RoundOpts, room_state: State<RoomState>,
+                        users_state: &RwLock<HashMap<String, User>>) -> Result<(), String> {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    handlers::require_event(&room_state, &room, ClientEvent::NewRound).await?;
+
+    if !room_state.round_done(&room).await {
+        return Err("the current round is not done".into());
+    }
+    // A closed retro's round is revealed on the way out, so it goes into the history with a result
+    let unrevealed = room_state.current_round.read().await.get(&room).is_some_and(|r| !r.flipped);
+    if unrevealed {
+        votes::reveal_round(&room_state, &room).await?;
+    }
+
+    let round_type: RoundType = round_opts
+        .round_type
+        .parse()
+        .map_err(|e| format!("invalid round type: {e}"))?;
+
+    if round_opts.candidates.iter().any(|c| c.contains(CANDIDATE_SEPARATOR)) {
+        return Err(format!("candidates cannot contain \"{CANDIDATE_SEPARATOR}\""));
+    }
+
+    let name = match (round_type, &round_opts.poll) {
+        (RoundType::Poll, Some(poll)) => {
+            if poll.question.trim().is_empty() {
+                return Err("polls need a question".into());
+            }
+            if round_opts.candidates.is_empty() && !poll.free_text {
+                return Err("polls need options or free-text answers".into());
+            }
+            if poll.deadline_minutes == Some(0) {
+                return Err("poll deadlines must be at least a minute away".into());
+            }
+            Some(poll.question.trim().to_owned())
+        }
+        (RoundType::Poll, None) => return Err("poll rounds need poll settings".into()),
+        (_, Some(_)) => return Err(format!("{round_type} rounds cannot have poll settings")),
+        (_, None) => None,
+    };
+
+    start_round(s, room.clone(), name, round_type, round_opts, &room_state, users_state).await;
+
+    let deadline = room_state
         .current_round
         .read()
         .await
         .get(&room)
-        .map_or(true, |r| r.flipped)
-    {
-        return Err("the current round is not done".into());
+        .and_then(|r| r.poll.as_ref())
+        .and_then(|p| p.deadline);
+    if let Some(deadline) = deadline {
+        schedule_poll_deadline(s, room, deadline, room_state.0);
     }
+    Ok(())
+}
+
+/// Reveals the room's poll once its deadline passes, unless it was revealed or replaced before
+fn schedule_poll_deadline(s: &SocketRef, room: String, deadline: DateTime<Utc>, room_state: &'static RoomState) {
+    let round_operators = s.within(room.clone());
+    let votes_operators = s.within(room.clone());
+    handlers::schedule_at(deadline, async move {
+        let still_open = room_state
+            .current_round
+            .read()
+            .await
+            .get(&room)
+            .is_some_and(|r| !r.flipped && r.poll.as_ref().is_some_and(|p| p.deadline == Some(deadline)));
+        if !still_open {
+            return;
+        }
+        let (current_round, hid_votes) = match votes::reveal_round(room_state, &room).await {
+            Ok(revealed) => revealed,
+            Err(error) => {
+                error!(error, room, "failed to reveal poll past its deadline");
+                return;
+            }
+        };
 
+        handlers::emit_to(round_operators, ServerEvent::CurrentRound(&current_round));
+        if hid_votes {
+            let votes = room_state.get_vote_dtos(&room).await;
+            handlers::emit_to(votes_operators, ServerEvent::Votes(&votes));
+        }
+    });
+}
+
+/// Archives the room's current round along with its votes and board and starts a new one,
+/// named after its number unless a name is given
+pub(crate) async fn start_round(s: &SocketRef, room: String, name: Option<String>, round_type: RoundType,
+                                round_opts: RoundOpts, room_state: &RoomState,
+                                users_state: &RwLock<HashMap<String, User>>) {
+    let (rounds, current_round) = open_round(&room, name, round_type, round_opts, room_state).await;
+
+    handlers::emit_within(s, room.clone(), ServerEvent::Rounds(&rounds));
+    handlers::emit_within(s, room.clone(), ServerEvent::Votes(&vec![]));
+    handlers::emit_within(s, room.clone(), ServerEvent::CurrentRound(&current_round));
+    let game = room_state.rooms.read().await.get(&room).map(|r| r.game);
+    if let Some(game) = game {
+        game.on_new_round(s, &room, room_state, users_state).await;
+    }
+}
+
+/// The changes of [start_round] to the room's state, returning the room's round history and its new round
+pub(crate) async fn open_round(room: &str, name: Option<String>, round_type: RoundType, round_opts: RoundOpts,
+                               room_state: &RoomState) -> (Vec<RoundDTO>, CurrentRoundDTO) {
     let votes = {
         let mut room_votes = room_state.votes.write().await;
-        let mut votes = room_votes.entry(room.clone()).or_default();
+        let votes = room_votes.entry(room.to_owned()).or_default();
         let round_votes: Vec<Vote> = votes.values().cloned().collect();
         votes.clear();
         round_votes
     };
 
-    let (rounds, current_round): (Vec<RoundDTO>, CurrentRoundDTO) = {
+    {
         let mut room_rounds = room_state.rounds.write().await;
-        let mut rounds = room_rounds.entry(room.clone()).or_default();
+        let rounds = room_rounds.entry(room.to_owned()).or_default();
 
         let mut current_rounds = room_state.current_round.write().await;
         let round_count = rounds.len()
-            + match current_rounds.get(&room) {
+            + match current_rounds.get(room) {
             Some(_) => 1,
             None => 0,
         };
-        let current_round = CurrentRound::new(round_count, round_opts);
-        if let Some(prev_round) = current_rounds.insert(room.clone(), current_round.clone())
+        let mut current_round = CurrentRound::new(round_count, round_type, round_opts);
+        if let Some(name) = name {
+            current_round.name = name;
+        }
+        if let Some(prev_round) = current_rounds.insert(room.to_owned(), current_round.clone())
         {
+            let board = room_state.boards.write().await.remove(room);
             rounds.push(Round {
                 votes,
                 name: prev_round.name,
+                result: prev_round.result,
+                board,
             });
         }
 
@@ -46,10 +154,27 @@ pub async fn handle_new(s: &SocketRef, room: String, round_opts: RoundOpts, room
             rounds.iter().cloned().map(Into::into).collect(),
             current_round.clone().into(),
         )
-    };
+    }
+}
 
-    handlers::emit_within(&s, room.clone(), ServerEvent::Rounds(&rounds));
-    handlers::emit_within(&s, room.clone(), ServerEvent::Votes(&vec![]));
-    handlers::emit_within(&s, room.clone(), ServerEvent::CurrentRound(&current_round));
-    Ok(())
+/// Exports the finished rounds of the room in the format of its game
+pub async fn handle_export(s: &SocketRef, room: String, room_state: State<RoomState>) -> Result<ExportDTO, String> {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    let room_info = handlers::require_event(&room_state, &room, ClientEvent::ExportRounds).await?;
+    let is_member = room_state
+        .members
+        .read()
+        .await
+        .get(&room)
+        .is_some_and(|m| m.contains_key(&user_id));
+    if !is_member {
+        return Err("you are not a member of this room".into());
+    }
+
+    let rounds = room_state.get_rounds(&room).await;
+    Ok(ExportDTO {
+        format: room_info.game.export_format().to_owned(),
+        content: room_info.game.export(&room_info, &rounds),
+        room,
+    })
 }
@@ -0,0 +1,155 @@
+use chrono::Utc;
+use socketioxide::extract::{AckSender, Data, SocketRef, State};
+use tracing::{debug, info};
+use uuid::Uuid;
+use crate::event::{ClientEvent, ItemIn, ItemRefIn, MoveItemIn, ServerEvent};
+use crate::handlers;
+use crate::handlers::EventResult;
+use crate::id::encode_id;
+use crate::state::{RoomState, Session};
+use crate::state::round::RoundDTO;
+use crate::state::sizing::{SizingBoard, SizingBoardDTO, SizingError, SizingItemDTO};
+
+/// Registers the handlers of the sizing board events on a newly connected socket
+pub(crate) fn register(s: &SocketRef) {
+    s.on(
+        ClientEvent::AddItem,
+        |socket: SocketRef,
+         Data(ItemIn { room, title }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::AddItem, room, "Received event");
+            handlers::ack_result(ack_sender, handle_add_item(&socket, room, title, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::MoveItem,
+        |socket: SocketRef,
+         Data(MoveItemIn { room, item_id, bucket, index }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::MoveItem, room, item_id, bucket, index, "Received event");
+            handlers::ack_result(ack_sender, handle_move_item(&socket, room, item_id, bucket, index, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::RemoveItem,
+        |socket: SocketRef,
+         Data(ItemRefIn { room, item_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::RemoveItem, room, item_id, "Received event");
+            handlers::ack_result(ack_sender, handle_remove_item(&socket, room, item_id, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::FinishSizing,
+        |socket: SocketRef,
+         Data::<String>(room),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::FinishSizing, room, "Received event");
+            handlers::ack_result(ack_sender, handle_finish_sizing(&socket, room, room_state).await);
+        },
+    );
+}
+
+/// Adds an item to the room's sizing board, which is set up with the room's deck when it is first
+/// used
+pub async fn handle_add_item(s: &SocketRef, room: String, title: String,
+                             room_state: State<RoomState>) -> Result<SizingItemDTO, String> {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    let room_info = handlers::require_event(&room_state, &room, ClientEvent::AddItem).await?;
+
+    let item = room_state
+        .sizing
+        .write()
+        .await
+        .entry(room.clone())
+        .or_insert_with(|| SizingBoard::new(room_info.deck))
+        .add(encode_id(&Uuid::new_v4()), user_id, &title, Utc::now())
+        .map_err(|e| e.to_string())?
+        .clone();
+
+    emit_sizing(s, &room, &room_state).await;
+    Ok(item.into())
+}
+
+pub async fn handle_move_item(s: &SocketRef, room: String, item_id: String, bucket: Option<String>, index: usize,
+                              room_state: State<RoomState>) -> EventResult {
+    update_sizing(&room_state, &room, ClientEvent::MoveItem, |board| board.move_item(&item_id, bucket, index)).await?;
+
+    emit_sizing(s, &room, &room_state).await;
+    Ok(())
+}
+
+pub async fn handle_remove_item(s: &SocketRef, room: String, item_id: String,
+                                room_state: State<RoomState>) -> EventResult {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    let is_facilitator = room_state.rooms.read().await.get(&room).is_some_and(|r| r.is_facilitator(&user_id));
+    update_sizing(&room_state, &room, ClientEvent::RemoveItem, |board| {
+        board.remove(&item_id, &user_id, is_facilitator)
+    }).await?;
+
+    emit_sizing(s, &room, &room_state).await;
+    Ok(())
+}
+
+/// Takes the items that were put into a bucket off the board and adds them to the room's round
+/// history, estimated at their bucket
+pub async fn handle_finish_sizing(s: &SocketRef, room: String, room_state: State<RoomState>) -> EventResult {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    let sized = update_sizing(&room_state, &room, ClientEvent::FinishSizing, SizingBoard::finish).await?;
+
+    // A board with nothing left on it picks up the room's deck again
+    let mut sizing = room_state.sizing.write().await;
+    if sizing.get(&room).is_some_and(|b| b.items.is_empty()) {
+        sizing.remove(&room);
+    }
+    drop(sizing);
+
+    let rounds: Vec<RoundDTO> = {
+        let mut room_rounds = room_state.rounds.write().await;
+        let rounds = room_rounds.entry(room.clone()).or_default();
+        rounds.extend(sized);
+        rounds.iter().cloned().map(Into::into).collect()
+    };
+
+    handlers::emit_within(s, room.clone(), ServerEvent::Rounds(&rounds));
+    emit_sizing(s, &room, &room_state).await;
+    Ok(())
+}
+
+/// Applies a change to the room's sizing board, turning sizing errors into event errors
+async fn update_sizing<T>(room_state: &RoomState, room: &str, event: ClientEvent,
+                          update: impl FnOnce(&mut SizingBoard) -> Result<T, SizingError>) -> Result<T, String> {
+    handlers::require_event(room_state, room, event).await?;
+    let mut sizing = room_state.sizing.write().await;
+    let Some(board) = sizing.get_mut(room) else {
+        return Err("no items have been added yet".into());
+    };
+    update(board).map_err(|e| e.to_string())
+}
+
+pub(crate) async fn sizing_view(room: &str, room_state: &RoomState) -> SizingBoardDTO {
+    if let Some(board) = room_state.sizing.read().await.get(room) {
+        return board.view(room);
+    }
+    let deck = room_state.rooms.read().await.get(room).map(|r| r.deck.clone()).unwrap_or_default();
+    SizingBoard::new(deck).view(room)
+}
+
+/// Sends a member joining the room its sizing board
+pub(crate) async fn send_sizing(socket: &SocketRef, room: &str, room_state: &RoomState) {
+    let sizing = sizing_view(room, room_state).await;
+    debug!(count = sizing.unplaced.len(), "Sending sizing board...");
+    handlers::emit_reply(socket, ServerEvent::Sizing(&sizing));
+}
+
+async fn emit_sizing(s: &SocketRef, room: &str, room_state: &RoomState) {
+    let sizing = sizing_view(room, room_state).await;
+    handlers::emit_within(s, room.to_owned(), ServerEvent::Sizing(&sizing));
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use socketioxide::extract::{AckSender, Data, SocketRef, State};
+use tracing::{debug, info};
+use uuid::Uuid;
+use crate::event::{ClientEvent, ServerEvent, StoryIn, StoryRefIn, StoryVoteIn};
+use crate::handlers;
+use crate::handlers::EventResult;
+use crate::id::encode_id;
+use crate::state::{RoomState, Session, Vote};
+use crate::state::round::RoundDTO;
+use crate::state::story::{Stories, StoriesDTO, Story, StoryDTO, DEFAULT_DUE_HOURS};
+use crate::state::vote::{Score, VoteDTO};
+
+/// Registers the handlers of the story events on a newly connected socket
+pub(crate) fn register(s: &SocketRef) {
+    s.on(
+        ClientEvent::OpenStory,
+        |socket: SocketRef,
+         Data(StoryIn { room, title, hours }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::OpenStory, room, hours, "Received event");
+            handlers::ack_result(ack_sender, handle_open_story(&socket, room, title, hours, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::VoteStory,
+        |socket: SocketRef,
+         Data(StoryVoteIn { room, story_id, score }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::VoteStory, room, story_id, "Received event");
+            handlers::ack_result(ack_sender, handle_vote_story(&socket, room, story_id, score, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::RetractStoryVote,
+        |socket: SocketRef,
+         Data(StoryRefIn { room, story_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::RetractStoryVote, room, story_id, "Received event");
+            handlers::ack_result(ack_sender, handle_retract_story_vote(&socket, room, story_id, room_state).await);
+        },
+    );
+
+    s.on(
+        ClientEvent::RevealStory,
+        |socket: SocketRef,
+         Data(StoryRefIn { room, story_id }),
+         room_state: State<RoomState>,
+         ack_sender: AckSender| async move {
+            info!(socket = %socket.id, event = %ClientEvent::RevealStory, room, story_id, "Received event");
+            handlers::ack_result(ack_sender, handle_reveal_story(&socket, room, story_id, room_state).await);
+        },
+    );
+}
+
+/// Opens a story for members to estimate with the room's deck whenever they come online, revealing
+/// it once it is due
+pub async fn handle_open_story(s: &SocketRef, room: String, title: String, hours: Option<u16>,
+                               room_state: State<RoomState>) -> Result<StoryDTO, String> {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    let room_info = handlers::require_event(&room_state, &room, ClientEvent::OpenStory).await?;
+
+    let story_id = encode_id(&Uuid::new_v4());
+    let story = Story::new(story_id.clone(), &title, room_info.deck, hours.unwrap_or(DEFAULT_DUE_HOURS), Utc::now())
+        .map_err(|e| e.to_string())?;
+    let due = story.due;
+    room_state.stories.write().await.entry(room.clone()).or_default().stories.push(story.clone());
+
+    emit_stories(s, &room, &room_state).await;
+    schedule_due(s, room, story_id, due, room_state.0);
+    Ok(story.view())
+}
+
+/// Records a member's vote on a story, revealing the story once every member has voted on it
+pub async fn handle_vote_story(s: &SocketRef, room: String, story_id: String, score: String,
+                               room_state: State<RoomState>) -> Result<VoteDTO, String> {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    let voters = room_state.get_voters(&room).await;
+    if !voters.contains(&user_id) {
+        return Err("only voting members can vote on stories".into());
+    }
+    let score: Score = score.parse().map_err(|error| format!("{error:?}"))?;
+    let room_info = handlers::require_event(&room_state, &room, ClientEvent::VoteStory).await?;
+
+    let vote = Vote { user_id: user_id.clone(), score };
+    let complete = update_stories(&room_state, &room, |stories| {
+        let story = stories.open_mut(&story_id).map_err(|e| e.to_string())?;
+        room_info
+            .game
+            .validate_score(&room_info, &story.round, &vote.score)
+            .map_err(|e| e.to_string())?;
+        story.votes.insert(user_id, vote.clone());
+        Ok(story.is_complete(&voters))
+    }).await?;
+
+    if complete {
+        let rounds = reveal_story(&room_state, &room, &story_id).await?;
+        handlers::emit_within(s, room.clone(), ServerEvent::Rounds(&rounds));
+    }
+    emit_stories(s, &room, &room_state).await;
+    Ok(vote.into())
+}
+
+pub async fn handle_retract_story_vote(s: &SocketRef, room: String, story_id: String,
+                                       room_state: State<RoomState>) -> EventResult {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
+    handlers::require_event(&room_state, &room, ClientEvent::RetractStoryVote).await?;
+    update_stories(&room_state, &room, |stories| {
+        stories.retract(&user_id, &story_id).map_err(|e| e.to_string())
+    }).await?;
+
+    emit_stories(s, &room, &room_state).await;
+    Ok(())
+}
+
+/// Reveals a story before it is due, without waiting for the members that haven't voted yet
+pub async fn handle_reveal_story(s: &SocketRef, room: String, story_id: String,
+                                 room_state: State<RoomState>) -> EventResult {
+    handlers::require_facilitator(s, &room_state, &room).await?;
+    handlers::require_event(&room_state, &room, ClientEvent::RevealStory).await?;
+    let rounds = reveal_story(&room_state, &room, &story_id).await?;
+
+    handlers::emit_within(s, room.clone(), ServerEvent::Rounds(&rounds));
+    emit_stories(s, &room, &room_state).await;
+    Ok(())
+}
+
+/// Has the room's game aggregate the votes on the story and adds it to the room's round history,
+/// returning the updated history
+async fn reveal_story(room_state: &RoomState, room: &str, story_id: &str) -> Result<Vec<RoundDTO>, String> {
+    let Some(game) = room_state.rooms.read().await.get(room).map(|r| r.game) else {
+        return Err(format!("room with ID \"{room}\" could not be found"));
+    };
+    let history = room_state.get_rounds(room).await;
+
+    let round = update_stories(room_state, room, |stories| {
+        let story = stories.open_mut(story_id).map_err(|e| e.to_string())?;
+        let result = game.reveal(&story.round, &story.votes.values().collect::<Vec<_>>(), &history);
+        story.round.reveal(result);
+        Ok(story.to_round())
+    }).await?;
+
+    let mut room_rounds = room_state.rounds.write().await;
+    let rounds = room_rounds.entry(room.to_owned()).or_default();
+    rounds.push(round);
+    Ok(rounds.iter().cloned().map(Into::into).collect())
+}
+
+/// Reveals the story once it is due, unless it was revealed before
+fn schedule_due(s: &SocketRef, room: String, story_id: String, due: DateTime<Utc>, room_state: &'static RoomState) {
+    let rounds_operators = s.within(room.clone());
+    let stories_operators = s.within(room.clone());
+    handlers::schedule_at(due, async move {
+        let rounds = match reveal_story(room_state, &room, &story_id).await {
+            Ok(rounds) => rounds,
+            // Stories revealed before they are due are left alone
+            Err(_) => return,
+        };
+        let stories = stories_view(&room, room_state).await;
+
+        handlers::emit_to(rounds_operators, ServerEvent::Rounds(&rounds));
+        handlers::emit_to(stories_operators, ServerEvent::Stories(&stories));
+    });
+}
+
+async fn update_stories<T>(room_state: &RoomState, room: &str,
+                           update: impl FnOnce(&mut Stories) -> Result<T, String>) -> Result<T, String> {
+    let mut stories = room_state.stories.write().await;
+    update(stories.entry(room.to_owned()).or_default())
+}
+
+pub(crate) async fn stories_view(room: &str, room_state: &RoomState) -> StoriesDTO {
+    room_state.stories.read().await.get(room).cloned().unwrap_or_default().view(room)
+}
+
+/// Sends a member joining the room its open and revealed stories
+pub(crate) async fn send_stories(socket: &SocketRef, room: &str, room_state: &RoomState) {
+    let stories = stories_view(room, room_state).await;
+    debug!(count = stories.open.len(), "Sending open stories...");
+    handlers::emit_reply(socket, ServerEvent::Stories(&stories));
+}
+
+async fn emit_stories(s: &SocketRef, room: &str, room_state: &RoomState) {
+    let stories = stories_view(room, room_state).await;
+    handlers::emit_within(s, room.to_owned(), ServerEvent::Stories(&stories));
+}
@@ -1,48 +1,97 @@
+use chrono::{Duration, Utc};
 use socketioxide::extract::{SocketRef, State};
-use crate::event::ServerEvent;
+use crate::event::{ClientEvent, ServerEvent};
 use crate::handlers;
-use crate::handlers::EventResult;
+use crate::handlers::{retro, EventResult};
 use crate::state::{RoomState, Session, Vote};
-use crate::state::round::CurrentRoundDTO;
-use crate::state::vote::VoteDTO;
+use crate::state::retro::RetroPhase;
+use crate::state::round::{CurrentRoundDTO, Poll};
+use crate::state::vote::{NudgeDTO, Score, VoteDTO};
+
+/// How many seconds members of a room have to wait between nudges
+const NUDGE_COOLDOWN_SECS: i64 = 30;
 
 pub async fn handle_end_vote(socket: &SocketRef, room: String, room_state: State<RoomState>) -> EventResult {
-    {
+    handlers::require_facilitator(socket, &room_state, &room).await?;
+    handlers::require_event(&room_state, &room, ClientEvent::EndVote).await?;
+
+    // Polls past their deadline can be revealed without waiting for everyone
+    let closed = room_state
+        .current_round
+        .read()
+        .await
+        .get(&room)
+        .and_then(|r| r.poll.as_ref())
+        .is_some_and(Poll::is_closed);
+    if !closed {
         let votes_state = room_state.votes.read().await;
         let Some(votes) = votes_state.get(&room) else {
             return Err("no votes for round".into());
         };
-        let members: Vec<_> = room_state
-            .members
-            .read()
-            .await
-            .get(&room)
-            .unwrap()
-            .iter()
-            .cloned()
-            .collect();
-        if members.iter().any(|m| votes.get(m).is_none()) {
+        let voters = room_state.get_voters(&room).await;
+        if voters.iter().any(|v| !votes.contains_key(v)) {
             return Err("not every member has voted".into());
         }
     }
 
+    let (current_round, hid_votes) = reveal_round(&room_state, &room).await?;
+
+    handlers::emit_within(socket, room.clone(), ServerEvent::CurrentRound(&current_round));
+    if hid_votes {
+        let votes = room_state.get_vote_dtos(&room).await;
+        handlers::emit_within(socket, room, ServerEvent::Votes(&votes));
+    }
+    Ok(())
+}
+
+/// Has the room's game aggregate the votes of the current round and flips it. Also returns whether
+/// the votes were hidden until now, in which case they need to be sent to the room again.
+pub(crate) async fn reveal_round(room_state: &RoomState, room: &str) -> Result<(CurrentRoundDTO, bool), String> {
+    let Some(game) = room_state.rooms.read().await.get(room).map(|r| r.game) else {
+        return Err(format!("room with ID \"{room}\" could not be found"));
+    };
+    let history = room_state.get_rounds(room).await;
+    let votes = room_state.get_votes(room).await;
+
+    let mut current_round_state = room_state.current_round.write().await;
+    let Some(current_round) = current_round_state.get_mut(room) else {
+        return Err("no current round".into());
+    };
+    let hid_votes = current_round.hides_votes();
+    let result = game.reveal(current_round, &votes.iter().collect::<Vec<_>>(), &history);
+    current_round.reveal(result);
+    Ok((current_round.clone().into(), hid_votes))
+}
+
+/// Updates the result of a round that shows it while it is running and sends it to the room
+pub(crate) async fn refresh_live_result(s: &SocketRef, room: &str, room_state: &RoomState) {
+    let votes = room_state.get_votes(room).await;
     let current_round: CurrentRoundDTO = {
         let mut current_round_state = room_state.current_round.write().await;
-        let Some(current_round) = current_round_state.get_mut(&room) else {
-            return Err("no current round".into());
+        let Some(current_round) = current_round_state.get_mut(room).filter(|r| r.shows_live_result()) else {
+            return;
         };
-        current_round.flipped = true;
+        current_round.result = Some(current_round.round_type.aggregate(current_round, &votes));
         current_round.clone().into()
     };
-
-    handlers::emit_within(&socket, room, ServerEvent::CurrentRound(&current_round));
-    Ok(())
+    handlers::emit_within(s, room.to_owned(), ServerEvent::CurrentRound(&current_round));
 }
 
 pub async fn handle_vote(s: &SocketRef, room: String, score: String,
                          room_state: State<RoomState>) -> EventResult {
     let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
 
+    let is_observer = room_state
+        .members
+        .read()
+        .await
+        .get(&room)
+        .and_then(|m| m.get(&user_id))
+        .is_some_and(|m| !m.is_voter());
+    if is_observer {
+        return Err("observers cannot vote".into());
+    }
+
     let score = match score.parse() {
         Ok(score) => score,
         Err(error) => {
@@ -50,6 +99,15 @@ pub async fn handle_vote(s: &SocketRef, room: String, score: String,
         }
     };
 
+    let room_info = handlers::require_event(&room_state, &room, ClientEvent::Vote).await?;
+    if let Some(current_round) = room_state.current_round.read().await.get(&room) {
+        room_info.game.validate_score(&room_info, current_round, &score).map_err(|e| e.to_string())?;
+    }
+    // Ideas are cards by another name, so in rooms with a board they can only be added while brainstorming
+    if matches!(score, Score::Idea { .. }) && room_info.template.is_some() {
+        retro::require_phase(&room_state, &room, ClientEvent::Vote, &[RetroPhase::Brainstorm]).await?;
+    }
+
     let vote = Vote {
         user_id: user_id.clone(),
         score,
@@ -64,19 +122,89 @@ pub async fn handle_vote(s: &SocketRef, room: String, score: String,
             .insert(user_id.clone(), vote.clone());
     }
     let dto: VoteDTO = vote.into();
-    handlers::emit_reply(&s, ServerEvent::Vote(&dto));
+    handlers::emit_reply(s, ServerEvent::Vote(&dto));
+
+    let votes = room_state.get_vote_dtos(&room).await;
+    handlers::emit_within(s, room.clone(), ServerEvent::Votes(&votes));
+    refresh_live_result(s, &room, &room_state).await;
+
+    Ok(())
+}
+
+pub async fn handle_retract_vote(s: &SocketRef, room: String,
+                                 room_state: State<RoomState>) -> EventResult {
+    let user_id = s.extensions.get::<Session>().unwrap().user_id.clone();
 
-    let votes: Vec<VoteDTO> = room_state
-        .votes
+    if room_state
+        .current_round
         .read()
         .await
         .get(&room)
-        .unwrap()
-        .values()
-        .cloned()
-        .map(Into::into)
-        .collect();
-    handlers::emit_within(&s, room, ServerEvent::Votes(&votes));
+        .is_some_and(|r| r.flipped)
+    {
+        return Err("the current round has already been revealed".into());
+    }
+
+    {
+        let mut votes_state = room_state.votes.write().await;
+        let Some(votes) = votes_state.get_mut(&room) else {
+            return Err("no votes for round".into());
+        };
+        if votes.remove(&user_id).is_none() {
+            return Err("you have not voted in this round".into());
+        }
+    }
+
+    let votes = room_state.get_vote_dtos(&room).await;
+    handlers::emit_within(s, room.clone(), ServerEvent::Votes(&votes));
+    refresh_live_result(s, &room, &room_state).await;
 
     Ok(())
 }
+
+/// Reminds every member that hasn't voted in the current round yet, returning their user IDs
+pub async fn handle_nudge(s: &SocketRef, room: String,
+                          room_state: State<RoomState>) -> Result<Vec<String>, String> {
+    let user_id = handlers::require_facilitator(s, &room_state, &room).await?;
+
+    let round = {
+        let current_round = room_state.current_round.read().await;
+        match current_round.get(&room) {
+            Some(round) if !round.flipped => round.name.clone(),
+            Some(_) => return Err("the current round has already been revealed".into()),
+            None => return Err("no current round".into()),
+        }
+    };
+
+    let missing: Vec<String> = {
+        let voters = room_state.get_voters(&room).await;
+        let votes = room_state.votes.read().await;
+        voters
+            .into_iter()
+            .filter(|v| !votes.get(&room).is_some_and(|votes| votes.contains_key(v)))
+            .collect()
+    };
+
+    if missing.is_empty() {
+        return Ok(missing);
+    }
+
+    {
+        let now = Utc::now();
+        let mut nudges = room_state.nudges.write().await;
+        if let Some(last_nudge) = nudges.get(&room) {
+            let wait = *last_nudge + Duration::seconds(NUDGE_COOLDOWN_SECS) - now;
+            if wait > Duration::zero() {
+                return Err(format!("members were just nudged, try again in {} seconds", wait.num_seconds() + 1));
+            }
+        }
+        nudges.insert(room.clone(), now);
+    }
+
+    let nudge = NudgeDTO { room, round, from_user_id: user_id };
+    for member in &missing {
+        handlers::emit_within(s, member.clone(), ServerEvent::Nudge(&nudge));
+    }
+
+    Ok(missing)
+}
@@ -1,28 +1,51 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+pub mod member;
 pub mod message;
 pub mod user;
 pub mod room;
 pub mod round;
+pub mod round_type;
+pub mod template;
 pub mod vote;
 pub mod game;
+pub mod ranking;
+pub mod health;
+pub mod lean_coffee;
+pub mod story;
+pub mod sizing;
+pub mod retro;
 
+pub use member::Member;
 pub use message::Message;
 pub use user::User;
 pub use room::Room;
 pub use round::Round;
 pub use vote::Vote;
+use crate::state::lean_coffee::LeanCoffee;
+use crate::state::retro::{ActionItem, RetroBoard, RetroPhase};
 use crate::state::round::CurrentRound;
+use crate::state::sizing::SizingBoard;
+use crate::state::story::Stories;
+use crate::state::vote::VoteDTO;
 
 pub type MessagesStore = HashMap<String, Vec<Message>>;
-pub type MembersStore = HashMap<String, BTreeSet<String>>;
+pub type MembersStore = HashMap<String, BTreeMap<String, Member>>;
 pub type RoomsStore = HashMap<String, Room>;
 pub type RoundsStore = HashMap<String, Vec<Round>>;
 pub type CurrentRoundStore = HashMap<String, CurrentRound>;
 pub type VotesStore = HashMap<String, HashMap<String, Vote>>;
+pub type NudgesStore = HashMap<String, DateTime<Utc>>;
+pub type BoardsStore = HashMap<String, RetroBoard>;
+pub type ActionsStore = HashMap<String, Vec<ActionItem>>;
+pub type PhasesStore = HashMap<String, RetroPhase>;
+pub type LeanCoffeeStore = HashMap<String, LeanCoffee>;
+pub type StoriesStore = HashMap<String, Stories>;
+pub type SizingStore = HashMap<String, SizingBoard>;
 
 
 #[derive(Default)]
@@ -33,6 +56,17 @@ pub struct RoomState {
     pub rounds: RwLock<RoundsStore>,
     pub votes: RwLock<VotesStore>,
     pub current_round: RwLock<CurrentRoundStore>,
+    /// When members of each room were last nudged to vote
+    pub nudges: RwLock<NudgesStore>,
+    pub boards: RwLock<BoardsStore>,
+    pub actions: RwLock<ActionsStore>,
+    /// The phase of the retro in each retro room
+    pub phases: RwLock<PhasesStore>,
+    pub lean_coffee: RwLock<LeanCoffeeStore>,
+    /// The stories estimated asynchronously in each room, next to its current round
+    pub stories: RwLock<StoriesStore>,
+    /// The sizing board of each room, set up with the room's deck once the first item is added
+    pub sizing: RwLock<SizingStore>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -72,6 +106,16 @@ impl RoomState {
         rounds.unwrap_or_default().into_iter().collect()
     }
 
+    /// Returns the votes to send to the members of the room, without scores if the current round hides them
+    pub async fn get_vote_dtos(&self, room: &str) -> Vec<VoteDTO> {
+        let hidden = self.current_round.read().await.get(room).is_some_and(CurrentRound::hides_votes);
+        self.get_votes(room)
+            .await
+            .into_iter()
+            .map(|v| if hidden { VoteDTO::hidden(v.user_id) } else { v.into() })
+            .collect()
+    }
+
     pub async fn get_votes(&self, room: &str) -> Vec<Vote> {
         let votes = self.votes.read().await.get(room).cloned();
         votes.unwrap_or_default().values().cloned().collect()
@@ -81,8 +125,78 @@ impl RoomState {
         self.rooms.read().await.get(room_id).unwrap().clone()
     }
 
-    pub async fn get_members(&self, room: &str) -> Vec<String> {
+    pub async fn get_members(&self, room: &str) -> Vec<Member> {
         let members = self.members.read().await.get(room).cloned();
-        members.unwrap_or_default().into_iter().rev().collect()
+        members.unwrap_or_default().into_values().collect()
+    }
+
+    /// Returns the IDs of the rooms the user is a member of
+    pub async fn get_member_rooms(&self, user_id: &str) -> Vec<String> {
+        self.members
+            .read()
+            .await
+            .iter()
+            .filter(|(_, members)| members.contains_key(user_id))
+            .map(|(room, _)| room.clone())
+            .collect()
+    }
+
+    /// Returns the IDs of the room members that are expected to vote
+    pub async fn get_voters(&self, room: &str) -> Vec<String> {
+        self.get_members(room)
+            .await
+            .into_iter()
+            .filter(Member::is_voter)
+            .map(|m| m.user_id)
+            .collect()
+    }
+
+    pub async fn get_phase(&self, room: &str) -> RetroPhase {
+        self.phases.read().await.get(room).copied().unwrap_or_default()
+    }
+
+    /// Whether the room's current round is over, so that a new one can start. Retro members add
+    /// cards rather than votes, so a closed retro ends its round without everyone having voted.
+    pub async fn round_done(&self, room: &str) -> bool {
+        let flipped = self.current_round.read().await.get(room).is_none_or(|r| r.flipped);
+        flipped || self.phases.read().await.get(room) == Some(&RetroPhase::Close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::round::RoundOpts;
+    use crate::state::round_type::RoundType;
+
+    async fn with_round(flipped: bool, phase: Option<RetroPhase>) -> RoomState {
+        let room_state = RoomState::default();
+        let round_opts = RoundOpts {
+            candidates: vec![],
+            max_votes: 1,
+            anonymous: false,
+            round_type: RoundType::Retro.to_string(),
+            poll: None,
+        };
+        let mut round = CurrentRound::new(0, RoundType::Retro, round_opts);
+        round.flipped = flipped;
+        room_state.current_round.write().await.insert("room".to_owned(), round);
+        if let Some(phase) = phase {
+            room_state.phases.write().await.insert("room".to_owned(), phase);
+        }
+        room_state
+    }
+
+    #[tokio::test]
+    async fn round_is_done_once_revealed() {
+        assert!(RoomState::default().round_done("room").await);
+        assert!(with_round(true, None).await.round_done("room").await);
+        assert!(!with_round(false, None).await.round_done("room").await);
+    }
+
+    #[tokio::test]
+    async fn closed_retro_ends_its_round_without_every_vote() {
+        assert!(with_round(false, Some(RetroPhase::Close)).await.round_done("room").await);
+        assert!(!with_round(false, Some(RetroPhase::ActionItems)).await.round_done("room").await);
     }
 }
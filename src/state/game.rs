@@ -1,7 +1,25 @@
-use std::fmt::Display;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::future::Future;
+use std::pin::Pin;
 use std::str::FromStr;
+use socketioxide::extract::SocketRef;
 use thiserror::Error;
+use tokio::sync::RwLock;
 use ts_rs::TS;
+use crate::event::ClientEvent;
+use crate::state::{Room, RoomState, Round, User, Vote};
+use crate::state::round::{CurrentRound, RoundResult};
+use crate::state::template::RetroTemplate;
+use crate::state::vote::{Score, ScoreError};
+
+pub mod async_effort;
+pub mod effort;
+pub mod fist_of_five;
+pub mod health_check;
+pub mod lean_coffee;
+pub mod relative_sizing;
+pub mod retro;
 
 #[derive(Error, Debug)]
 pub enum ParseError {
@@ -9,21 +27,134 @@ pub enum ParseError {
     UnknownVariant(String)
 }
 
-#[derive(serde::Serialize, Clone, Debug, TS)]
+/// The name of every game a room can be created for, one per entry of [GAMES]
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
 #[ts(export, export_to = "client/src/types/ppapi/")]
-pub enum Game {
+pub enum GameName {
     Effort,
     Retro,
+    FistOfFive,
+    HealthCheck,
+    LeanCoffee,
+    AsyncEffort,
+    RelativeSizing,
+}
+
+impl GameName {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameName::Effort => "effort",
+            GameName::Retro => "retro",
+            GameName::FistOfFive => "fist-of-five",
+            GameName::HealthCheck => "health-check",
+            GameName::LeanCoffee => "lean-coffee",
+            GameName::AsyncEffort => "async-effort",
+            GameName::RelativeSizing => "relative-sizing",
+        }
+    }
+}
+
+impl Display for GameName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Events about the rounds of a room and their votes, handled by every game that plays rounds
+pub const ROUND_EVENTS: &[ClientEvent] = &[
+    ClientEvent::Vote,
+    ClientEvent::RetractVote,
+    ClientEvent::EndVote,
+    ClientEvent::Nudge,
+    ClientEvent::NewRound,
+    ClientEvent::ExportRounds,
+];
+
+/// The work a game does on the sockets of a room when its members join it or a new round starts
+pub type HookFuture<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+/// The rules of what is played in a room. New games only need to implement this trait and be
+/// added to [GAMES] to become available when creating a room.
+pub trait Game: Debug + Send + Sync {
+    /// The name the game is created with, see [FromStr]
+    fn name(&self) -> GameName;
+
+    /// The sets of client events that rooms playing this game handle, on top of the ones that
+    /// manage the room and its members
+    fn events(&self) -> &'static [&'static [ClientEvent]];
+
+    /// Whether rooms playing this game handle the client event
+    fn accepts(&self, event: ClientEvent) -> bool {
+        self.events().iter().any(|events| events.contains(&event))
+    }
+
+    /// The columns new rooms lay out their retro board in, for games played on one
+    fn template(&self) -> Option<RetroTemplate> {
+        None
+    }
+
+    /// Checks that a vote's score is valid for the current round of the room
+    fn validate_score(&self, _room: &Room, round: &CurrentRound, score: &Score) -> Result<(), ScoreError> {
+        round.validate_score(score)
+    }
+
+    /// Aggregates the votes of a round when it is revealed, given the room's earlier rounds
+    fn reveal(&self, round: &CurrentRound, votes: &[&Vote], _history: &[Round]) -> RoundResult {
+        round.round_type.aggregate(round, votes.iter().copied())
+    }
+
+    /// The MIME type of [Game::export]
+    fn export_format(&self) -> &'static str;
+
+    /// Writes the finished rounds of the room in a format that can be pasted into other tools
+    fn export(&self, room: &Room, rounds: &[Round]) -> String;
+
+    /// Registers the handlers of the game's own events on a newly connected socket
+    fn register_events(&self, _socket: &SocketRef) {}
+
+    /// Sends a member that joins the room what they need to follow the game, besides its rounds
+    fn on_join<'a>(&'a self, _socket: &'a SocketRef, _room: &'a str, _room_state: &'a RoomState,
+                   _users_state: &'a RwLock<HashMap<String, User>>) -> HookFuture<'a> {
+        Box::pin(async {})
+    }
+
+    /// Prepares the room for the round that was just started in it
+    fn on_new_round<'a>(&'a self, _socket: &'a SocketRef, _room: &'a str, _room_state: &'a RoomState,
+                        _users_state: &'a RwLock<HashMap<String, User>>) -> HookFuture<'a> {
+        Box::pin(async {})
+    }
 }
 
-impl FromStr for Game {
+/// Every game a room can be created for
+pub const GAMES: &[&dyn Game] = &[
+    &effort::Effort,
+    &retro::Retro,
+    &fist_of_five::FistOfFive,
+    &health_check::HealthCheck,
+    &lean_coffee::LeanCoffee,
+    &async_effort::AsyncEffort,
+    &relative_sizing::RelativeSizing,
+];
+
+impl FromStr for &'static dyn Game {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "effort" => Ok(Game::Effort),
-            "retro" => Ok(Game::Retro),
-            _ => Err(ParseError::UnknownVariant(s.into()))
-        }
+        let name = s.to_lowercase();
+        GAMES
+            .iter()
+            .copied()
+            .find(|game| game.name().as_str() == name)
+            .ok_or_else(|| ParseError::UnknownVariant(s.into()))
     }
-}
\ No newline at end of file
+}
+
+/// The finished rounds of a room, exported by its game
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct ExportDTO {
+    pub room: String,
+    pub format: String,
+    pub content: String,
+}
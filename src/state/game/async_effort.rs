@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+use socketioxide::extract::SocketRef;
+use tokio::sync::RwLock;
+use crate::event::ClientEvent;
+use crate::handlers;
+use crate::state::{Room, RoomState, Round, User};
+use super::{effort, Game, GameName, HookFuture};
+
+/// Events that act on the stories of an asynchronous estimation room
+const STORY_EVENTS: &[ClientEvent] = &[
+    ClientEvent::OpenStory,
+    ClientEvent::VoteStory,
+    ClientEvent::RetractStoryVote,
+    ClientEvent::RevealStory,
+];
+
+/// Estimation spread over days, with several stories open at once that members vote on whenever
+/// they come online. Revealed stories join the room's round history.
+#[derive(Debug)]
+pub struct AsyncEffort;
+
+impl Game for AsyncEffort {
+    fn name(&self) -> GameName {
+        GameName::AsyncEffort
+    }
+
+    fn events(&self) -> &'static [&'static [ClientEvent]] {
+        &[super::ROUND_EVENTS, STORY_EVENTS]
+    }
+
+    fn export_format(&self) -> &'static str {
+        "text/csv"
+    }
+
+    fn export(&self, _room: &Room, rounds: &[Round]) -> String {
+        effort::export_csv(rounds)
+    }
+
+    fn register_events(&self, socket: &SocketRef) {
+        handlers::stories::register(socket);
+    }
+
+    fn on_join<'a>(&'a self, socket: &'a SocketRef, room: &'a str, room_state: &'a RoomState,
+                   _users_state: &'a RwLock<HashMap<String, User>>) -> HookFuture<'a> {
+        Box::pin(handlers::stories::send_stories(socket, room, room_state))
+    }
+}
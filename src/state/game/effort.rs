@@ -0,0 +1,86 @@
+use std::fmt::Write;
+use crate::event::ClientEvent;
+use crate::state::{Room, Round};
+use crate::state::round::RoundResult;
+use super::{Game, GameName};
+
+/// Estimation and voting rounds, without a retro board
+#[derive(Debug)]
+pub struct Effort;
+
+impl Game for Effort {
+    fn name(&self) -> GameName {
+        GameName::Effort
+    }
+
+    fn events(&self) -> &'static [&'static [ClientEvent]] {
+        &[super::ROUND_EVENTS]
+    }
+
+    fn export_format(&self) -> &'static str {
+        "text/csv"
+    }
+
+    fn export(&self, _room: &Room, rounds: &[Round]) -> String {
+        export_csv(rounds)
+    }
+}
+
+/// One row per option of each revealed round, with its votes, average percentage or Borda points
+pub(super) fn export_csv(rounds: &[Round]) -> String {
+    let mut csv = String::from("round,option,value\n");
+    for round in rounds {
+        let Some(result) = &round.result else {
+            continue;
+        };
+        for (option, value) in result_rows(result) {
+            let _ = writeln!(csv, "{},{},{}", csv_field(&round.name), csv_field(&option), value);
+        }
+    }
+    csv
+}
+
+fn result_rows(result: &RoundResult) -> Vec<(String, String)> {
+    match result {
+        RoundResult::Estimate { average, tally }
+        | RoundResult::FistOfFive { average, distribution: tally, .. } => average
+            .map(|average| ("average".to_owned(), average.to_string()))
+            .into_iter()
+            .chain(tally.iter().map(|t| (t.candidate.clone(), t.votes.to_string())))
+            .collect(),
+        RoundResult::PickOne { tally } | RoundResult::MultiPick { tally } | RoundResult::Retro { tally } => {
+            tally.iter().map(|t| (t.candidate.clone(), t.votes.to_string())).collect()
+        }
+        RoundResult::Percent { allocations } => allocations
+            .iter()
+            .map(|a| (a.candidate.clone(), a.average_percent.to_string()))
+            .collect(),
+        RoundResult::Ranking { borda, .. } => borda
+            .iter()
+            .map(|p| (p.candidate.clone(), p.points.to_string()))
+            .collect(),
+        RoundResult::Thumbs { up, sideways, down, .. } => [("up", up), ("sideways", sideways), ("down", down)]
+            .into_iter()
+            .map(|(thumb, votes)| (thumb.to_owned(), votes.to_string()))
+            .collect(),
+        RoundResult::Poll { tally, answers } => tally
+            .iter()
+            .map(|t| (t.candidate.clone(), t.votes.to_string()))
+            .chain(answers.iter().map(|a| ("answer".to_owned(), a.clone())))
+            .collect(),
+        RoundResult::Sized { estimate } => vec![("estimate".to_owned(), estimate.clone())],
+        RoundResult::HealthCheck { categories } => categories
+            .iter()
+            .map(|c| (c.category.clone(), c.rating.map_or("", |r| r.as_str()).to_owned()))
+            .collect(),
+    }
+}
+
+/// Quotes a CSV field if it contains characters that would break the row
+pub(super) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
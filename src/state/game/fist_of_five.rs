@@ -0,0 +1,81 @@
+use crate::event::ClientEvent;
+use crate::state::{Room, Round, Vote};
+use crate::state::round::{CandidateTally, Concern, CurrentRound, RoundResult};
+use crate::state::vote::{Score, ScoreError};
+use super::{effort, Game, GameName};
+
+/// The most fingers a member can hold up
+const MAX_FINGERS: u8 = 5;
+
+/// Members holding up this many fingers or fewer are flagged for discussion
+const CONCERN_FINGERS: u8 = 2;
+
+/// Confidence votes from 0 to 5 fingers, optionally with a comment
+#[derive(Debug)]
+pub struct FistOfFive;
+
+impl Game for FistOfFive {
+    fn name(&self) -> GameName {
+        GameName::FistOfFive
+    }
+
+    fn events(&self) -> &'static [&'static [ClientEvent]] {
+        &[super::ROUND_EVENTS]
+    }
+
+    fn validate_score(&self, _room: &Room, _round: &CurrentRound, score: &Score) -> Result<(), ScoreError> {
+        match fingers(score) {
+            Some((fingers, _)) if fingers > MAX_FINGERS => {
+                Err(ScoreError::OutOfRange { max: MAX_FINGERS, value: fingers })
+            }
+            Some(_) => Ok(()),
+            None => Err(ScoreError::UnsupportedByGame(self.name())),
+        }
+    }
+
+    fn reveal(&self, _round: &CurrentRound, votes: &[&Vote], _history: &[Round]) -> RoundResult {
+        let mut distribution: Vec<_> = (0..=MAX_FINGERS)
+            .map(|fingers| CandidateTally { candidate: fingers.to_string(), votes: 0 })
+            .collect();
+        let mut concerns = vec![];
+        let mut total = 0u32;
+        let mut count = 0u32;
+        for vote in votes {
+            let Some((fingers, comment)) = fingers(&vote.score) else {
+                continue;
+            };
+            if let Some(t) = distribution.get_mut(fingers as usize) {
+                t.votes += 1;
+            }
+            total += fingers as u32;
+            count += 1;
+            if fingers <= CONCERN_FINGERS {
+                concerns.push(Concern { user_id: vote.user_id.clone(), fingers, comment: comment.cloned() });
+            }
+        }
+        concerns.sort_by_key(|c| c.fingers);
+
+        RoundResult::FistOfFive {
+            average: (count > 0).then(|| total as f32 / count as f32),
+            distribution,
+            concerns,
+        }
+    }
+
+    fn export_format(&self) -> &'static str {
+        "text/csv"
+    }
+
+    fn export(&self, _room: &Room, rounds: &[Round]) -> String {
+        effort::export_csv(rounds)
+    }
+}
+
+/// The number of fingers and the comment of a confidence vote
+fn fingers(score: &Score) -> Option<(u8, Option<&String>)> {
+    match score {
+        Score::Number(n) => Some((*n, None)),
+        Score::Commented { value, comment } => Some((*value, Some(comment).filter(|c| !c.trim().is_empty()))),
+        _ => None,
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use crate::event::ClientEvent;
+use crate::state::{Room, Round, Vote};
+use crate::state::health::{CategoryHealth, CategoryVote, HealthRating, HEALTH_CATEGORIES};
+use crate::state::round::{CurrentRound, RoundResult};
+use crate::state::vote::{Score, ScoreError};
+use super::{Game, GameName};
+use super::effort::csv_field;
+
+/// A squad health check, each member rating every category green, yellow or red along with a trend
+#[derive(Debug)]
+pub struct HealthCheck;
+
+impl Game for HealthCheck {
+    fn name(&self) -> GameName {
+        GameName::HealthCheck
+    }
+
+    fn events(&self) -> &'static [&'static [ClientEvent]] {
+        &[super::ROUND_EVENTS]
+    }
+
+    fn validate_score(&self, _room: &Room, _round: &CurrentRound, score: &Score) -> Result<(), ScoreError> {
+        let Score::Health(votes) = score else {
+            return Err(ScoreError::UnsupportedByGame(self.name()));
+        };
+        let mut seen = HashSet::new();
+        for vote in votes {
+            if !HEALTH_CATEGORIES.iter().any(|(key, _)| *key == vote.category) {
+                return Err(ScoreError::UnknownCategory(vote.category.clone()));
+            }
+            if !seen.insert(vote.category.as_str()) {
+                return Err(ScoreError::DuplicateCategory(vote.category.clone()));
+            }
+        }
+        match HEALTH_CATEGORIES.iter().find(|(key, _)| !seen.contains(key)) {
+            Some((key, _)) => Err(ScoreError::MissingCategory(key.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Rates each category and adds its ratings from the room's earlier health checks
+    fn reveal(&self, _round: &CurrentRound, votes: &[&Vote], history: &[Round]) -> RoundResult {
+        let category_votes: Vec<&CategoryVote> = votes
+            .iter()
+            .filter_map(|v| match &v.score {
+                Score::Health(votes) => Some(votes),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        let past: Vec<&Vec<CategoryHealth>> = history
+            .iter()
+            .filter_map(|r| match &r.result {
+                Some(RoundResult::HealthCheck { categories }) => Some(categories),
+                _ => None,
+            })
+            .collect();
+
+        let categories = HEALTH_CATEGORIES
+            .iter()
+            .map(|(key, title)| {
+                let votes: Vec<&CategoryVote> = category_votes.iter().copied().filter(|v| v.category == *key).collect();
+                let history: Vec<Option<HealthRating>> = past
+                    .iter()
+                    .map(|categories| categories.iter().find(|c| c.category == *key).and_then(|c| c.rating))
+                    .collect();
+                CategoryHealth::new(key, title, &votes, history)
+            })
+            .collect();
+        RoundResult::HealthCheck { categories }
+    }
+
+    fn export_format(&self) -> &'static str {
+        "text/csv"
+    }
+
+    /// One row per category of each health check, so sessions can be compared in a spreadsheet
+    fn export(&self, _room: &Room, rounds: &[Round]) -> String {
+        let mut csv = String::from("round,category,green,yellow,red,rating,trend\n");
+        for round in rounds {
+            let Some(RoundResult::HealthCheck { categories }) = &round.result else {
+                continue;
+            };
+            for c in categories {
+                let rating = c.rating.map_or("", |r| r.as_str());
+                let _ = writeln!(csv, "{},{},{},{},{},{rating},{}",
+                                 csv_field(&round.name), csv_field(&c.title), c.green, c.yellow, c.red, c.trend);
+            }
+        }
+        csv
+    }
+}
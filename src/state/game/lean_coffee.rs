@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use socketioxide::extract::SocketRef;
+use tokio::sync::RwLock;
+use crate::event::ClientEvent;
+use crate::handlers;
+use crate::state::{Room, RoomState, Round, User};
+use super::{effort, Game, GameName, HookFuture};
+
+/// Events that act on the topics of a Lean Coffee room
+const TOPIC_EVENTS: &[ClientEvent] = &[
+    ClientEvent::ProposeTopic,
+    ClientEvent::DotTopic,
+    ClientEvent::UndotTopic,
+    ClientEvent::StartTopic,
+    ClientEvent::ExtendTopic,
+    ClientEvent::FinishTopic,
+];
+
+/// Lean Coffee, discussing the most dotted topics in timeboxes. Each timebox is a thumbs round named
+/// after its topic, so the room's round history lists what was discussed.
+#[derive(Debug)]
+pub struct LeanCoffee;
+
+impl Game for LeanCoffee {
+    fn name(&self) -> GameName {
+        GameName::LeanCoffee
+    }
+
+    fn events(&self) -> &'static [&'static [ClientEvent]] {
+        &[super::ROUND_EVENTS, TOPIC_EVENTS]
+    }
+
+    fn export_format(&self) -> &'static str {
+        "text/csv"
+    }
+
+    fn export(&self, _room: &Room, rounds: &[Round]) -> String {
+        effort::export_csv(rounds)
+    }
+
+    fn register_events(&self, socket: &SocketRef) {
+        handlers::lean_coffee::register(socket);
+    }
+
+    fn on_join<'a>(&'a self, socket: &'a SocketRef, room: &'a str, room_state: &'a RoomState,
+                   _users_state: &'a RwLock<HashMap<String, User>>) -> HookFuture<'a> {
+        Box::pin(handlers::lean_coffee::send_lean_coffee(socket, room, room_state))
+    }
+}
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+use socketioxide::extract::SocketRef;
+use tokio::sync::RwLock;
+use crate::event::ClientEvent;
+use crate::handlers;
+use crate::state::{Room, RoomState, Round, User};
+use super::{effort, Game, GameName, HookFuture};
+
+/// Events that act on the sizing board of a relative sizing room
+const SIZING_EVENTS: &[ClientEvent] = &[
+    ClientEvent::AddItem,
+    ClientEvent::MoveItem,
+    ClientEvent::RemoveItem,
+    ClientEvent::FinishSizing,
+];
+
+/// Silent relative sizing of a backlog, with members moving items onto a shared scale instead of
+/// voting on them one by one. Each sized item becomes a round estimated at its bucket.
+#[derive(Debug)]
+pub struct RelativeSizing;
+
+impl Game for RelativeSizing {
+    fn name(&self) -> GameName {
+        GameName::RelativeSizing
+    }
+
+    fn events(&self) -> &'static [&'static [ClientEvent]] {
+        &[super::ROUND_EVENTS, SIZING_EVENTS]
+    }
+
+    fn export_format(&self) -> &'static str {
+        "text/csv"
+    }
+
+    fn export(&self, _room: &Room, rounds: &[Round]) -> String {
+        effort::export_csv(rounds)
+    }
+
+    fn register_events(&self, socket: &SocketRef) {
+        handlers::sizing::register(socket);
+    }
+
+    fn on_join<'a>(&'a self, socket: &'a SocketRef, room: &'a str, room_state: &'a RoomState,
+                   _users_state: &'a RwLock<HashMap<String, User>>) -> HookFuture<'a> {
+        Box::pin(handlers::sizing::send_sizing(socket, room, room_state))
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use socketioxide::extract::SocketRef;
+use tokio::sync::RwLock;
+use crate::event::ClientEvent;
+use crate::handlers;
+use crate::state::{Room, RoomState, Round, User};
+use crate::state::retro::{Card, RetroBoard};
+use crate::state::round::CurrentRound;
+use crate::state::template::RetroTemplate;
+use crate::state::vote::{Score, ScoreError};
+use super::{Game, GameName, HookFuture};
+
+/// Events that act on a room's retro board, its phases or its action items
+const BOARD_EVENTS: &[ClientEvent] = &[
+    ClientEvent::Reveal,
+    ClientEvent::AddCard,
+    ClientEvent::EditCard,
+    ClientEvent::DeleteCard,
+    ClientEvent::GroupCards,
+    ClientEvent::MoveCard,
+    ClientEvent::RenameGroup,
+    ClientEvent::MergeCards,
+    ClientEvent::StartDotVote,
+    ClientEvent::AddDot,
+    ClientEvent::RemoveDot,
+    ClientEvent::EndDotVote,
+    ClientEvent::CreateAction,
+    ClientEvent::AssignAction,
+    ClientEvent::CompleteAction,
+    ClientEvent::ReopenAction,
+    ClientEvent::SetPhase,
+];
+
+/// Retrospectives on a board of cards, with action items carried over between them
+#[derive(Debug)]
+pub struct Retro;
+
+impl Game for Retro {
+    fn name(&self) -> GameName {
+        GameName::Retro
+    }
+
+    fn events(&self) -> &'static [&'static [ClientEvent]] {
+        &[super::ROUND_EVENTS, BOARD_EVENTS]
+    }
+
+    fn template(&self) -> Option<RetroTemplate> {
+        Some(RetroTemplate::default())
+    }
+
+    /// Checks that ideas are filed under one of the columns of the room's board
+    fn validate_score(&self, room: &Room, round: &CurrentRound, score: &Score) -> Result<(), ScoreError> {
+        round.validate_score(score)?;
+        if let Score::Idea { column, .. } = score {
+            if !room.has_retro_column(column) {
+                return Err(ScoreError::UnknownColumn(column.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn export_format(&self) -> &'static str {
+        "text/markdown"
+    }
+
+    /// A section per retro, listing its groups with their cards and then the ungrouped revealed cards
+    /// of each column, with the dots they received and the duplicates merged into them
+    fn export(&self, room: &Room, rounds: &[Round]) -> String {
+        let template = room.template.clone().unwrap_or_default();
+        let mut markdown = format!("# {}\n", room.name);
+        for round in rounds {
+            let Some(board) = round.board.as_ref().filter(|b| b.revealed) else {
+                continue;
+            };
+            let _ = write!(markdown, "\n## {}\n", round.name);
+            for group in &board.groups {
+                let _ = match dots(board, &group.group_id) {
+                    0 => write!(markdown, "\n### {}\n\n", group.title),
+                    dots => write!(markdown, "\n### {} ({dots} dots)\n\n", group.title),
+                };
+                for card in board.cards.iter().filter(|c| c.group_id.as_ref() == Some(&group.group_id)) {
+                    write_card(&mut markdown, board, card);
+                }
+            }
+            for column in &template.columns {
+                let _ = write!(markdown, "\n### {} {}\n\n", column.emoji, column.title);
+                for card in board.cards.iter().filter(|c| c.column == column.key && c.group_id.is_none()) {
+                    write_card(&mut markdown, board, card);
+                }
+            }
+        }
+        markdown
+    }
+
+    fn register_events(&self, socket: &SocketRef) {
+        handlers::retro::register(socket);
+    }
+
+    fn on_join<'a>(&'a self, socket: &'a SocketRef, room: &'a str, room_state: &'a RoomState,
+                   users_state: &'a RwLock<HashMap<String, User>>) -> HookFuture<'a> {
+        Box::pin(handlers::retro::send_retro(socket, room, room_state, users_state))
+    }
+
+    fn on_new_round<'a>(&'a self, socket: &'a SocketRef, room: &'a str, room_state: &'a RoomState,
+                        users_state: &'a RwLock<HashMap<String, User>>) -> HookFuture<'a> {
+        Box::pin(handlers::retro::start_retro(socket, room, room_state, users_state))
+    }
+}
+
+/// Writes a card as a list item, with the duplicates merged into it nested below
+fn write_card(markdown: &mut String, board: &RetroBoard, card: &Card) {
+    let _ = match dots(board, &card.card_id) {
+        0 => writeln!(markdown, "- {}", card.text),
+        dots => writeln!(markdown, "- {} ({dots} dots)", card.text),
+    };
+    for merged in &card.merged {
+        let _ = writeln!(markdown, "  - {}", merged.text);
+    }
+}
+
+fn dots(board: &RetroBoard, item_id: &str) -> u32 {
+    board
+        .dot_vote
+        .as_ref()
+        .and_then(|d| d.tally().into_iter().find(|t| t.item_id == item_id))
+        .map_or(0, |t| t.dots)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use chrono::Utc;
+    use crate::state::retro::{CardGroup, MergedCard};
+    use super::*;
+
+    fn card(card_id: &str, column: &str, text: &str, group_id: Option<&str>) -> Card {
+        Card {
+            card_id: card_id.to_owned(),
+            author: "author".to_owned(),
+            column: column.to_owned(),
+            text: text.to_owned(),
+            created: Utc::now(),
+            group_id: group_id.map(str::to_owned),
+            merged: vec![],
+        }
+    }
+
+    #[test]
+    fn export_lists_groups_with_their_cards_and_merged_duplicates() {
+        let room = Room {
+            room_id: "room".to_owned(),
+            name: "Team".to_owned(),
+            game: &Retro,
+            facilitators: BTreeSet::new(),
+            banned: BTreeSet::new(),
+            template: None,
+            deck: vec![],
+        };
+        let column = RetroTemplate::default().columns[0].key.clone();
+        let mut grouped = card("a", &column, "Pairing helped", Some("g"));
+        grouped.merged.push(MergedCard { author: "other".to_owned(), text: "More pairing".to_owned() });
+        let board = RetroBoard {
+            cards: vec![grouped, card("b", &column, "Shipped on time", None)],
+            groups: vec![CardGroup { group_id: "g".to_owned(), title: "Collaboration".to_owned() }],
+            revealed: true,
+            dot_vote: None,
+        };
+        let round = Round { name: "Sprint 1".to_owned(), votes: vec![], result: None, board: Some(board) };
+
+        let markdown = Retro.export(&room, &[round]);
+        assert!(markdown.contains("### Collaboration\n\n- Pairing helped\n  - More pairing\n"), "{markdown}");
+        let column_section = &markdown[markdown.rfind("Collaboration").unwrap()..];
+        assert!(column_section.contains("- Shipped on time\n"), "{markdown}");
+        assert_eq!(markdown.matches("- Pairing helped").count(), 1, "{markdown}");
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use ts_rs::TS;
+use crate::state::game::ParseError;
+
+/// Separates a category's rating from its trend, e.g. `fun=green/up`
+pub const TREND_SEPARATOR: char = '/';
+
+/// The categories of the squad health check, as key and title
+pub const HEALTH_CATEGORIES: [(&str, &str); 10] = [
+    ("easy-to-release", "Easy to release"),
+    ("suitable-process", "Suitable process"),
+    ("tech-quality", "Tech quality"),
+    ("value", "Delivering value"),
+    ("speed", "Speed"),
+    ("mission", "Mission"),
+    ("fun", "Fun"),
+    ("learning", "Learning"),
+    ("support", "Support"),
+    ("pawns-or-players", "Pawns or players"),
+];
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub enum HealthRating {
+    Red,
+    Yellow,
+    Green,
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub enum Trend {
+    Down,
+    #[default]
+    Flat,
+    Up,
+}
+
+/// A single member's rating of one category
+#[derive(Clone, Debug)]
+pub struct CategoryVote {
+    pub category: String,
+    pub rating: HealthRating,
+    pub trend: Trend,
+}
+
+/// How the team rated one category in a health check
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct CategoryHealth {
+    pub category: String,
+    pub title: String,
+    pub green: u32,
+    pub yellow: u32,
+    pub red: u32,
+    /// The most common rating, the worse one on a tie
+    pub rating: Option<HealthRating>,
+    /// Whether more members think the category is getting better or worse
+    pub trend: Trend,
+    /// The category's rating in the room's earlier health checks, oldest first
+    pub history: Vec<Option<HealthRating>>,
+}
+
+impl CategoryHealth {
+    pub fn new(category: &str, title: &str, votes: &[&CategoryVote], history: Vec<Option<HealthRating>>) -> Self {
+        let count = |rating| votes.iter().filter(|v| v.rating == rating).count() as u32;
+        let (green, yellow, red) = (count(HealthRating::Green), count(HealthRating::Yellow), count(HealthRating::Red));
+        let rating = [(HealthRating::Red, red), (HealthRating::Yellow, yellow), (HealthRating::Green, green)]
+            .into_iter()
+            .filter(|(_, votes)| *votes > 0)
+            .max_by_key(|(rating, votes)| (*votes, std::cmp::Reverse(*rating)))
+            .map(|(rating, _)| rating);
+        let net: i32 = votes
+            .iter()
+            .map(|v| match v.trend {
+                Trend::Up => 1,
+                Trend::Flat => 0,
+                Trend::Down => -1,
+            })
+            .sum();
+        Self {
+            category: category.to_owned(),
+            title: title.to_owned(),
+            green,
+            yellow,
+            red,
+            rating,
+            trend: match net {
+                n if n > 0 => Trend::Up,
+                n if n < 0 => Trend::Down,
+                _ => Trend::Flat,
+            },
+            history,
+        }
+    }
+}
+
+impl HealthRating {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthRating::Red => "red",
+            HealthRating::Yellow => "yellow",
+            HealthRating::Green => "green",
+        }
+    }
+}
+
+impl Trend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Trend::Down => "down",
+            Trend::Flat => "flat",
+            Trend::Up => "up",
+        }
+    }
+}
+
+impl FromStr for HealthRating {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "red" => Ok(HealthRating::Red),
+            "yellow" => Ok(HealthRating::Yellow),
+            "green" => Ok(HealthRating::Green),
+            _ => Err(ParseError::UnknownVariant(s.into()))
+        }
+    }
+}
+
+impl FromStr for Trend {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "down" => Ok(Trend::Down),
+            "flat" => Ok(Trend::Flat),
+            "up" => Ok(Trend::Up),
+            _ => Err(ParseError::UnknownVariant(s.into()))
+        }
+    }
+}
+
+impl Display for HealthRating {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Display for Trend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CategoryVote {
+    type Err = ParseError;
+
+    /// Parses `category=rating` or `category=rating/trend`, the trend being flat if left out
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((category, value)) = s.split_once(super::vote::VALUE_SEPARATOR) else {
+            return Err(ParseError::UnknownVariant(s.into()));
+        };
+        let (rating, trend) = match value.split_once(TREND_SEPARATOR) {
+            Some((rating, trend)) => (rating.trim().parse()?, trend.trim().parse()?),
+            None => (value.trim().parse()?, Trend::default()),
+        };
+        Ok(Self { category: category.trim().to_owned(), rating, trend })
+    }
+}
+
+impl Display for CategoryVote {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "{}{}{}{TREND_SEPARATOR}{}",
+            self.category,
+            super::vote::VALUE_SEPARATOR,
+            self.rating,
+            self.trend
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote(rating: HealthRating, trend: Trend) -> CategoryVote {
+        CategoryVote { category: "fun".to_owned(), rating, trend }
+    }
+
+    fn aggregate(votes: &[CategoryVote]) -> CategoryHealth {
+        CategoryHealth::new("fun", "Fun", &votes.iter().collect::<Vec<_>>(), vec![])
+    }
+
+    #[test]
+    fn counts_each_rating_and_picks_the_most_common() {
+        let health = aggregate(&[
+            vote(HealthRating::Green, Trend::Flat),
+            vote(HealthRating::Green, Trend::Flat),
+            vote(HealthRating::Red, Trend::Flat),
+        ]);
+        assert_eq!((health.green, health.yellow, health.red), (2, 0, 1));
+        assert_eq!(health.rating, Some(HealthRating::Green));
+    }
+
+    #[test]
+    fn ties_go_to_the_worse_rating() {
+        let health = aggregate(&[vote(HealthRating::Green, Trend::Flat), vote(HealthRating::Yellow, Trend::Flat)]);
+        assert_eq!(health.rating, Some(HealthRating::Yellow));
+    }
+
+    #[test]
+    fn trend_follows_the_net_direction() {
+        let up = aggregate(&[vote(HealthRating::Green, Trend::Up), vote(HealthRating::Green, Trend::Up), vote(HealthRating::Green, Trend::Down)]);
+        assert_eq!(up.trend, Trend::Up);
+        let even = aggregate(&[vote(HealthRating::Green, Trend::Up), vote(HealthRating::Green, Trend::Down)]);
+        assert_eq!(even.trend, Trend::Flat);
+    }
+
+    #[test]
+    fn no_votes_means_no_rating() {
+        let health = aggregate(&[]);
+        assert_eq!(health.rating, None);
+        assert_eq!(health.trend, Trend::Flat);
+    }
+}
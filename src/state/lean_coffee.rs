@@ -0,0 +1,332 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use chrono::{DateTime, Duration, Utc};
+use thiserror::Error;
+use ts_rs::TS;
+use crate::state::game::ParseError;
+
+/// How many topics each member can dot, a topic taking several of their dots if they like
+pub const DOTS_PER_MEMBER: usize = 3;
+
+/// Length of a topic's first timebox when the facilitator doesn't pick one
+pub const DEFAULT_TIMEBOX_MINUTES: u8 = 5;
+
+/// Length of the extra timebox when the room votes to keep going
+pub const DEFAULT_EXTENSION_MINUTES: u8 = 3;
+
+#[derive(Error, Debug)]
+pub enum LeanCoffeeError {
+    #[error("topic with ID \"{0}\" could not be found")]
+    UnknownTopic(String),
+    #[error("topic \"{0}\" has already been discussed")]
+    NotQueued(String),
+    #[error("you have already used all of your {DOTS_PER_MEMBER} dots")]
+    OutOfDots,
+    #[error("you have not put a dot on that topic")]
+    NoDot,
+    #[error("there are no more topics to discuss")]
+    EmptyQueue,
+    #[error("no topic is being discussed")]
+    NoDiscussion,
+    #[error("timeboxes need to be at least a minute long")]
+    EmptyTimebox,
+}
+
+/// A thumb vote on whether to keep discussing a topic
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub enum Thumb {
+    Up,
+    Sideways,
+    Down,
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub enum TopicStatus {
+    Queued,
+    Discussing,
+    Done,
+}
+
+#[derive(Clone, Debug)]
+pub struct Topic {
+    pub topic_id: String,
+    pub author: String,
+    pub title: String,
+    pub created: DateTime<Utc>,
+    /// The IDs of the members that dotted the topic, once per dot
+    pub dots: Vec<String>,
+    pub status: TopicStatus,
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct TopicDTO {
+    #[serde(rename = "topicID")]
+    pub topic_id: String,
+    pub author: String,
+    pub title: String,
+    pub created: String,
+    pub dots: Vec<String>,
+    pub status: TopicStatus,
+}
+
+impl From<Topic> for TopicDTO {
+    fn from(value: Topic) -> Self {
+        Self {
+            topic_id: value.topic_id,
+            author: value.author,
+            title: value.title,
+            created: value.created.to_rfc3339(),
+            dots: value.dots,
+            status: value.status,
+        }
+    }
+}
+
+/// The topic being discussed and its timebox
+#[derive(Clone, Debug)]
+pub struct Discussion {
+    pub topic_id: String,
+    pub started: DateTime<Utc>,
+    pub ends: DateTime<Utc>,
+    /// Set by the server once the timebox has run out, so the room can vote on keeping going
+    pub expired: bool,
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct DiscussionDTO {
+    #[serde(rename = "topicID")]
+    pub topic_id: String,
+    pub started: String,
+    pub ends: String,
+    pub expired: bool,
+}
+
+impl From<Discussion> for DiscussionDTO {
+    fn from(value: Discussion) -> Self {
+        Self {
+            topic_id: value.topic_id,
+            started: value.started.to_rfc3339(),
+            ends: value.ends.to_rfc3339(),
+            expired: value.expired,
+        }
+    }
+}
+
+/// The topics of a Lean Coffee room
+#[derive(Clone, Debug, Default)]
+pub struct LeanCoffee {
+    pub topics: Vec<Topic>,
+    pub discussion: Option<Discussion>,
+}
+
+impl LeanCoffee {
+    fn topic_mut(&mut self, topic_id: &str) -> Result<&mut Topic, LeanCoffeeError> {
+        self.topics
+            .iter_mut()
+            .find(|t| t.topic_id == topic_id)
+            .ok_or_else(|| LeanCoffeeError::UnknownTopic(topic_id.to_owned()))
+    }
+
+    fn queued_mut(&mut self, topic_id: &str) -> Result<&mut Topic, LeanCoffeeError> {
+        let topic = self.topic_mut(topic_id)?;
+        if topic.status != TopicStatus::Queued {
+            return Err(LeanCoffeeError::NotQueued(topic.title.clone()));
+        }
+        Ok(topic)
+    }
+
+    pub fn add_dot(&mut self, user_id: &str, topic_id: &str) -> Result<(), LeanCoffeeError> {
+        let used = self
+            .topics
+            .iter()
+            .filter(|t| t.status == TopicStatus::Queued)
+            .flat_map(|t| &t.dots)
+            .filter(|d| *d == user_id)
+            .count();
+        if used >= DOTS_PER_MEMBER {
+            return Err(LeanCoffeeError::OutOfDots);
+        }
+        self.queued_mut(topic_id)?.dots.push(user_id.to_owned());
+        Ok(())
+    }
+
+    pub fn remove_dot(&mut self, user_id: &str, topic_id: &str) -> Result<(), LeanCoffeeError> {
+        let topic = self.queued_mut(topic_id)?;
+        let index = topic.dots.iter().position(|d| d == user_id).ok_or(LeanCoffeeError::NoDot)?;
+        topic.dots.remove(index);
+        Ok(())
+    }
+
+    /// The topics still to be discussed, most dotted first and otherwise in the order they were proposed
+    pub fn queue(&self) -> Vec<&Topic> {
+        let mut queue: Vec<_> = self.topics.iter().filter(|t| t.status == TopicStatus::Queued).collect();
+        queue.sort_by_key(|t| (std::cmp::Reverse(t.dots.len()), t.created));
+        queue
+    }
+
+    /// Closes the current discussion and starts on the given topic, or the top of the queue
+    pub fn start(&mut self, topic_id: Option<&str>, minutes: u8, now: DateTime<Utc>) -> Result<&Topic, LeanCoffeeError> {
+        if minutes == 0 {
+            return Err(LeanCoffeeError::EmptyTimebox);
+        }
+        let topic_id = match topic_id {
+            Some(topic_id) => self.queued_mut(topic_id)?.topic_id.clone(),
+            None => self.queue().first().ok_or(LeanCoffeeError::EmptyQueue)?.topic_id.clone(),
+        };
+        let _ = self.finish();
+
+        self.discussion = Some(Discussion {
+            topic_id: topic_id.clone(),
+            started: now,
+            ends: now + Duration::minutes(minutes as i64),
+            expired: false,
+        });
+        let topic = self.topic_mut(&topic_id)?;
+        topic.status = TopicStatus::Discussing;
+        Ok(topic)
+    }
+
+    /// Gives the current topic another timebox, returning it
+    pub fn extend(&mut self, minutes: u8, now: DateTime<Utc>) -> Result<&Topic, LeanCoffeeError> {
+        if minutes == 0 {
+            return Err(LeanCoffeeError::EmptyTimebox);
+        }
+        let discussion = self.discussion.as_mut().ok_or(LeanCoffeeError::NoDiscussion)?;
+        discussion.ends = now + Duration::minutes(minutes as i64);
+        discussion.expired = false;
+        let topic_id = discussion.topic_id.clone();
+        self.topic_mut(&topic_id).map(|t| &*t)
+    }
+
+    pub fn finish(&mut self) -> Result<(), LeanCoffeeError> {
+        let discussion = self.discussion.take().ok_or(LeanCoffeeError::NoDiscussion)?;
+        self.topic_mut(&discussion.topic_id)?.status = TopicStatus::Done;
+        Ok(())
+    }
+
+    /// Marks the timebox as run out, unless the discussion has moved on or been extended since.
+    /// Returns whether anything changed.
+    pub fn expire(&mut self, topic_id: &str, ends: DateTime<Utc>) -> bool {
+        match &mut self.discussion {
+            Some(d) if d.topic_id == topic_id && d.ends == ends && !d.expired => {
+                d.expired = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn view(&self, room: &str) -> LeanCoffeeDTO {
+        let done = self.topics.iter().filter(|t| t.status == TopicStatus::Done);
+        LeanCoffeeDTO {
+            room: room.to_owned(),
+            queue: self.queue().into_iter().cloned().map(Into::into).collect(),
+            discussing: self
+                .discussion
+                .as_ref()
+                .and_then(|d| self.topics.iter().find(|t| t.topic_id == d.topic_id))
+                .cloned()
+                .map(Into::into),
+            discussion: self.discussion.clone().map(Into::into),
+            done: done.cloned().map(Into::into).collect(),
+        }
+    }
+}
+
+/// The topics of a Lean Coffee room, sent to its members
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct LeanCoffeeDTO {
+    pub room: String,
+    pub queue: Vec<TopicDTO>,
+    pub discussing: Option<TopicDTO>,
+    pub discussion: Option<DiscussionDTO>,
+    pub done: Vec<TopicDTO>,
+}
+
+impl Thumb {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Thumb::Up => "up",
+            Thumb::Sideways => "sideways",
+            Thumb::Down => "down",
+        }
+    }
+}
+
+impl FromStr for Thumb {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "up" => Ok(Thumb::Up),
+            "sideways" => Ok(Thumb::Sideways),
+            "down" => Ok(Thumb::Down),
+            _ => Err(ParseError::UnknownVariant(s.into()))
+        }
+    }
+}
+
+impl Display for Thumb {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topic(topic_id: &str, created: i64, dots: usize) -> Topic {
+        Topic {
+            topic_id: topic_id.to_owned(),
+            author: "author".to_owned(),
+            title: topic_id.to_owned(),
+            created: DateTime::from_timestamp(created, 0).unwrap(),
+            dots: vec!["member".to_owned(); dots],
+            status: TopicStatus::Queued,
+        }
+    }
+
+    fn queue_ids(board: &LeanCoffee) -> Vec<&str> {
+        board.queue().into_iter().map(|t| t.topic_id.as_str()).collect()
+    }
+
+    #[test]
+    fn queue_puts_the_most_dotted_topics_first() {
+        let board = LeanCoffee {
+            topics: vec![topic("a", 0, 1), topic("b", 1, 3), topic("c", 2, 2)],
+            discussion: None,
+        };
+        assert_eq!(queue_ids(&board), ["b", "c", "a"]);
+    }
+
+    #[test]
+    fn queue_breaks_ties_by_the_order_topics_were_proposed() {
+        let board = LeanCoffee {
+            topics: vec![topic("late", 5, 1), topic("early", 1, 1), topic("undotted", 0, 0)],
+            discussion: None,
+        };
+        assert_eq!(queue_ids(&board), ["early", "late", "undotted"]);
+    }
+
+    #[test]
+    fn queue_leaves_out_topics_being_or_already_discussed() {
+        let mut board = LeanCoffee {
+            topics: vec![topic("a", 0, 2), topic("b", 1, 1), topic("c", 2, 0)],
+            discussion: None,
+        };
+        let now = DateTime::from_timestamp(10, 0).unwrap();
+        board.start(None, 5, now).unwrap();
+        assert_eq!(queue_ids(&board), ["b", "c"]);
+        board.start(None, 5, now).unwrap();
+        assert_eq!(queue_ids(&board), ["c"]);
+        assert_eq!(board.topics[0].status, TopicStatus::Done);
+    }
+}
@@ -0,0 +1,58 @@
+use ts_rs::TS;
+use crate::state::user::UserDTO;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub enum MemberRole {
+    /// Votes in the room's rounds
+    #[default]
+    Participant,
+    /// Follows the room without voting
+    Observer,
+}
+
+/// Whether a member is currently around in a room
+#[derive(serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub enum Presence {
+    /// Not connected at all
+    #[default]
+    Away,
+    /// Connected, but not to this room
+    Idle,
+    /// Connected to this room
+    Online,
+}
+
+#[derive(Clone, Debug)]
+pub struct Member {
+    pub user_id: String,
+    pub role: MemberRole,
+}
+
+impl Member {
+    pub fn new(user_id: String, role: MemberRole) -> Self {
+        Self { user_id, role }
+    }
+
+    pub fn is_voter(&self) -> bool {
+        self.role == MemberRole::Participant
+    }
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct MemberDTO {
+    #[serde(flatten)]
+    pub user: UserDTO,
+    pub role: MemberRole,
+    pub presence: Presence,
+}
+
+impl MemberDTO {
+    pub fn new(user: UserDTO, member: &Member, presence: Presence) -> Self {
+        Self { user, role: member.role, presence }
+    }
+}
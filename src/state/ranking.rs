@@ -0,0 +1,136 @@
+use ts_rs::TS;
+use super::round::CandidateTally;
+
+/// One counting round of an instant-runoff tally
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct RunoffRound {
+    pub tally: Vec<CandidateTally>,
+    pub eliminated: Vec<String>,
+}
+
+/// Borda count points of a single candidate
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct CandidatePoints {
+    pub candidate: String,
+    pub points: u32,
+}
+
+/// Runs an instant-runoff count over the ballots, returning the winner (if there is a single one)
+/// and the counting rounds that led to it
+pub fn instant_runoff(candidates: &[String], ballots: &[&Vec<String>]) -> (Option<String>, Vec<RunoffRound>) {
+    let mut remaining: Vec<&String> = candidates.iter().collect();
+    let mut rounds = vec![];
+
+    while !remaining.is_empty() {
+        let mut tally: Vec<CandidateTally> = remaining
+            .iter()
+            .map(|&candidate| CandidateTally { candidate: candidate.clone(), votes: 0 })
+            .collect();
+        let mut total = 0;
+        for ballot in ballots {
+            let Some(choice) = ballot.iter().find(|c| remaining.contains(c)) else {
+                continue;
+            };
+            if let Some(t) = tally.iter_mut().find(|t| &t.candidate == choice) {
+                t.votes += 1;
+                total += 1;
+            }
+        }
+
+        if let Some(leader) = tally.iter().find(|t| t.votes * 2 > total) {
+            let winner = leader.candidate.clone();
+            rounds.push(RunoffRound { tally, eliminated: vec![] });
+            return (Some(winner), rounds);
+        }
+
+        let fewest = tally.iter().map(|t| t.votes).min().unwrap_or_default();
+        let eliminated: Vec<String> = tally
+            .iter()
+            .filter(|t| t.votes == fewest)
+            .map(|t| t.candidate.clone())
+            .collect();
+        let tied = eliminated.len() == remaining.len();
+        if !tied {
+            remaining.retain(|c| !eliminated.contains(c));
+        }
+        rounds.push(RunoffRound { tally, eliminated: if tied { vec![] } else { eliminated } });
+        if tied {
+            break;
+        }
+    }
+
+    (None, rounds)
+}
+
+/// Scores each candidate by the Borda count, where a ballot gives `n - 1` points to its first
+/// choice, `n - 2` to the second and so on. Candidates are sorted by points, highest first.
+pub fn borda(candidates: &[String], ballots: &[&Vec<String>]) -> Vec<CandidatePoints> {
+    let n = candidates.len() as u32;
+    let mut points: Vec<CandidatePoints> = candidates
+        .iter()
+        .map(|candidate| CandidatePoints { candidate: candidate.clone(), points: 0 })
+        .collect();
+    for ballot in ballots {
+        for (rank, choice) in ballot.iter().enumerate() {
+            if let Some(p) = points.iter_mut().find(|p| &p.candidate == choice) {
+                p.points += n.saturating_sub(rank as u32 + 1);
+            }
+        }
+    }
+    points.sort_by_key(|p| std::cmp::Reverse(p.points));
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(names: &[&str]) -> Vec<String> {
+        names.iter().map(|&n| n.to_owned()).collect()
+    }
+
+    #[test]
+    fn majority_wins_in_the_first_round() {
+        let ballots = [candidates(&["a", "b"]), candidates(&["a", "b"]), candidates(&["b", "a"])];
+        let (winner, rounds) = instant_runoff(&candidates(&["a", "b"]), &ballots.iter().collect::<Vec<_>>());
+        assert_eq!(winner.as_deref(), Some("a"));
+        assert_eq!(rounds.len(), 1);
+        assert!(rounds[0].eliminated.is_empty());
+    }
+
+    #[test]
+    fn eliminated_votes_move_to_the_next_choice() {
+        let ballots = [
+            candidates(&["a", "b", "c"]),
+            candidates(&["a", "b", "c"]),
+            candidates(&["b", "a", "c"]),
+            candidates(&["b", "a", "c"]),
+            candidates(&["c", "b", "a"]),
+        ];
+        let (winner, rounds) = instant_runoff(&candidates(&["a", "b", "c"]), &ballots.iter().collect::<Vec<_>>());
+        assert_eq!(winner.as_deref(), Some("b"));
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0].eliminated, candidates(&["c"]));
+        let b = rounds[1].tally.iter().find(|t| t.candidate == "b").unwrap();
+        assert_eq!(b.votes, 3);
+    }
+
+    #[test]
+    fn full_tie_has_no_winner() {
+        let ballots = [candidates(&["a", "b"]), candidates(&["b", "a"])];
+        let (winner, rounds) = instant_runoff(&candidates(&["a", "b"]), &ballots.iter().collect::<Vec<_>>());
+        assert_eq!(winner, None);
+        assert_eq!(rounds.len(), 1);
+        assert!(rounds[0].eliminated.is_empty());
+    }
+
+    #[test]
+    fn borda_gives_points_by_rank_highest_first() {
+        let ballots = [candidates(&["a", "b", "c"]), candidates(&["b", "a", "c"]), candidates(&["b", "c", "a"])];
+        let points = borda(&candidates(&["a", "b", "c"]), &ballots.iter().collect::<Vec<_>>());
+        let points: Vec<_> = points.iter().map(|p| (p.candidate.as_str(), p.points)).collect();
+        assert_eq!(points, [("b", 5), ("a", 3), ("c", 1)]);
+    }
+}
@@ -0,0 +1,603 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use chrono::{DateTime, NaiveDate, Utc};
+use thiserror::Error;
+use ts_rs::TS;
+use crate::state::user::UserDTO;
+
+#[derive(Error, Debug)]
+pub enum BoardError {
+    #[error("card with ID \"{0}\" could not be found")]
+    UnknownCard(String),
+    #[error("group with ID \"{0}\" could not be found")]
+    UnknownGroup(String),
+    #[error("cards can only be grouped after they have been revealed")]
+    NotRevealed,
+    #[error("a card cannot be merged into itself")]
+    SelfMerge,
+    #[error("a group needs at least one card")]
+    EmptyGroup,
+    #[error("no card or group with ID \"{0}\" could be found")]
+    UnknownItem(String),
+    #[error("there is no dot vote running")]
+    NoDotVote,
+    #[error("you have already used all of your {0} dots")]
+    OutOfDots(u8),
+    #[error("you have not put a dot on that")]
+    NoDot,
+    #[error("that is not possible during the {0} phase")]
+    WrongPhase(RetroPhase),
+    #[error("the retro has been closed")]
+    Closed,
+    #[error("the retro cannot move from the {0} phase to the {1} phase")]
+    InvalidTransition(RetroPhase, RetroPhase),
+}
+
+#[derive(Clone, Debug)]
+pub struct Card {
+    pub card_id: String,
+    pub author: String,
+    pub column: String,
+    pub text: String,
+    pub created: DateTime<Utc>,
+    pub group_id: Option<String>,
+    /// Duplicates that have been merged into this card
+    pub merged: Vec<MergedCard>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct MergedCard {
+    pub author: String,
+    pub text: String,
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct CardDTO {
+    #[serde(rename = "cardID")]
+    pub card_id: String,
+    pub author: String,
+    pub column: String,
+    pub text: String,
+    pub created: String,
+    #[serde(rename = "groupID")]
+    pub group_id: Option<String>,
+    pub merged: Vec<MergedCard>,
+}
+
+impl From<Card> for CardDTO {
+    fn from(value: Card) -> Self {
+        Self {
+            card_id: value.card_id,
+            author: value.author,
+            column: value.column,
+            text: value.text,
+            created: value.created.to_rfc3339(),
+            group_id: value.group_id,
+            merged: value.merged,
+        }
+    }
+}
+
+/// A titled cluster of cards on a retro board
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct CardGroup {
+    #[serde(rename = "groupID")]
+    pub group_id: String,
+    pub title: String,
+}
+
+/// The cards of a retro room, hidden from everyone but their authors until revealed
+#[derive(Clone, Debug, Default)]
+pub struct RetroBoard {
+    pub cards: Vec<Card>,
+    pub groups: Vec<CardGroup>,
+    pub revealed: bool,
+    pub dot_vote: Option<DotVote>,
+}
+
+/// Dots that members put on cards and groups to pick what to discuss
+#[derive(Clone, Debug)]
+pub struct DotVote {
+    pub dots_per_member: u8,
+    /// The IDs of the cards and groups each member put a dot on, once per dot
+    pub dots: HashMap<String, Vec<String>>,
+    pub open: bool,
+}
+
+/// Number of dots a card or group received
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct DotTally {
+    #[serde(rename = "itemID")]
+    pub item_id: String,
+    pub dots: u32,
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct DotVoteDTO {
+    pub dots_per_member: u8,
+    pub open: bool,
+    /// The items the receiving member put their dots on
+    pub my_dots: Vec<String>,
+    /// Dots per item, most dotted first. Empty until the dot vote has ended.
+    pub tally: Vec<DotTally>,
+}
+
+impl DotVote {
+    pub fn tally(&self) -> Vec<DotTally> {
+        let mut tally: Vec<DotTally> = vec![];
+        for item_id in self.dots.values().flatten() {
+            match tally.iter_mut().find(|t| &t.item_id == item_id) {
+                Some(t) => t.dots += 1,
+                None => tally.push(DotTally { item_id: item_id.clone(), dots: 1 }),
+            }
+        }
+        tally.sort_by_key(|t| std::cmp::Reverse(t.dots));
+        tally
+    }
+
+    fn view_for(&self, user_id: Option<&str>) -> DotVoteDTO {
+        DotVoteDTO {
+            dots_per_member: self.dots_per_member,
+            open: self.open,
+            my_dots: user_id.and_then(|u| self.dots.get(u)).cloned().unwrap_or_default(),
+            tally: if self.open { vec![] } else { self.tally() },
+        }
+    }
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct RetroBoardDTO {
+    pub revealed: bool,
+    pub cards: Vec<CardDTO>,
+    /// Number of cards by other members that are not revealed yet
+    pub hidden_cards: u32,
+    pub groups: Vec<CardGroup>,
+    pub dot_vote: Option<DotVoteDTO>,
+}
+
+impl RetroBoard {
+    pub fn card_mut(&mut self, card_id: &str) -> Option<&mut Card> {
+        self.cards.iter_mut().find(|c| c.card_id == card_id)
+    }
+
+    fn require_card(&mut self, card_id: &str) -> Result<&mut Card, BoardError> {
+        self.cards
+            .iter_mut()
+            .find(|c| c.card_id == card_id)
+            .ok_or_else(|| BoardError::UnknownCard(card_id.to_owned()))
+    }
+
+    /// Puts the cards into a new group with the given title
+    pub fn group_cards(&mut self, group_id: String, title: String, card_ids: &[String]) -> Result<CardGroup, BoardError> {
+        if !self.revealed {
+            return Err(BoardError::NotRevealed);
+        }
+        if card_ids.is_empty() {
+            return Err(BoardError::EmptyGroup);
+        }
+        if let Some(unknown) = card_ids.iter().find(|id| !self.cards.iter().any(|c| &c.card_id == *id)) {
+            return Err(BoardError::UnknownCard(unknown.clone()));
+        }
+        for card in self.cards.iter_mut().filter(|c| card_ids.contains(&c.card_id)) {
+            card.group_id = Some(group_id.clone());
+        }
+        let group = CardGroup { group_id, title };
+        self.groups.push(group.clone());
+        self.prune_groups();
+        Ok(group)
+    }
+
+    /// Moves a card into another group, or out of any group if none is given
+    pub fn move_card(&mut self, card_id: &str, group_id: Option<String>) -> Result<(), BoardError> {
+        if !self.revealed {
+            return Err(BoardError::NotRevealed);
+        }
+        if let Some(group_id) = &group_id {
+            if !self.groups.iter().any(|g| &g.group_id == group_id) {
+                return Err(BoardError::UnknownGroup(group_id.clone()));
+            }
+        }
+        self.require_card(card_id)?.group_id = group_id;
+        self.prune_groups();
+        Ok(())
+    }
+
+    pub fn rename_group(&mut self, group_id: &str, title: String) -> Result<(), BoardError> {
+        let group = self.groups
+            .iter_mut()
+            .find(|g| g.group_id == group_id)
+            .ok_or_else(|| BoardError::UnknownGroup(group_id.to_owned()))?;
+        group.title = title;
+        Ok(())
+    }
+
+    /// Folds duplicate cards into a single card, which keeps its own group
+    pub fn merge_cards(&mut self, card_id: &str, duplicate_ids: &[String]) -> Result<(), BoardError> {
+        if !self.revealed {
+            return Err(BoardError::NotRevealed);
+        }
+        if duplicate_ids.iter().any(|id| id == card_id) {
+            return Err(BoardError::SelfMerge);
+        }
+        self.require_card(card_id)?;
+        if let Some(unknown) = duplicate_ids.iter().find(|id| !self.cards.iter().any(|c| &c.card_id == *id)) {
+            return Err(BoardError::UnknownCard(unknown.clone()));
+        }
+
+        let (duplicates, cards): (Vec<Card>, Vec<Card>) = std::mem::take(&mut self.cards)
+            .into_iter()
+            .partition(|c| duplicate_ids.contains(&c.card_id));
+        self.cards = cards;
+        let card = self.require_card(card_id)?;
+        for duplicate in duplicates {
+            card.merged.push(MergedCard { author: duplicate.author, text: duplicate.text });
+            card.merged.extend(duplicate.merged);
+        }
+        // Dots on the duplicates count for the card they were merged into
+        for dot in self.dot_vote.iter_mut().flat_map(|d| d.dots.values_mut()).flatten() {
+            if duplicate_ids.contains(dot) {
+                *dot = card_id.to_owned();
+            }
+        }
+        self.prune_groups();
+        Ok(())
+    }
+
+    /// Starts a new dot vote, discarding the dots of any earlier one
+    pub fn start_dot_vote(&mut self, dots_per_member: u8) -> Result<(), BoardError> {
+        if !self.revealed {
+            return Err(BoardError::NotRevealed);
+        }
+        self.dot_vote = Some(DotVote { dots_per_member, dots: HashMap::new(), open: true });
+        Ok(())
+    }
+
+    pub fn add_dot(&mut self, user_id: &str, item_id: &str) -> Result<(), BoardError> {
+        if !self.has_item(item_id) {
+            return Err(BoardError::UnknownItem(item_id.to_owned()));
+        }
+        let dot_vote = self.dot_vote.as_mut().filter(|d| d.open).ok_or(BoardError::NoDotVote)?;
+        let dots = dot_vote.dots.entry(user_id.to_owned()).or_default();
+        if dots.len() >= dot_vote.dots_per_member as usize {
+            return Err(BoardError::OutOfDots(dot_vote.dots_per_member));
+        }
+        dots.push(item_id.to_owned());
+        Ok(())
+    }
+
+    pub fn remove_dot(&mut self, user_id: &str, item_id: &str) -> Result<(), BoardError> {
+        let dot_vote = self.dot_vote.as_mut().filter(|d| d.open).ok_or(BoardError::NoDotVote)?;
+        let dots = dot_vote.dots.get_mut(user_id).ok_or(BoardError::NoDot)?;
+        let index = dots.iter().position(|d| d == item_id).ok_or(BoardError::NoDot)?;
+        dots.remove(index);
+        Ok(())
+    }
+
+    /// Closes the dot vote and orders the cards and groups by their dots, most dotted first
+    pub fn end_dot_vote(&mut self) -> Result<(), BoardError> {
+        let dot_vote = self.dot_vote.as_mut().filter(|d| d.open).ok_or(BoardError::NoDotVote)?;
+        dot_vote.open = false;
+        let tally = dot_vote.tally();
+        let dots_for = |item_id: &str| tally.iter().find(|t| t.item_id == item_id).map_or(0, |t| t.dots);
+        self.cards.sort_by_key(|c| std::cmp::Reverse(dots_for(&c.card_id)));
+        self.groups.sort_by_key(|g| std::cmp::Reverse(dots_for(&g.group_id)));
+        Ok(())
+    }
+
+    fn has_item(&self, item_id: &str) -> bool {
+        self.cards.iter().any(|c| c.card_id == item_id) || self.groups.iter().any(|g| g.group_id == item_id)
+    }
+
+    /// Whether members currently see different versions of the board
+    pub fn is_personal(&self) -> bool {
+        !self.revealed || self.dot_vote.as_ref().is_some_and(|d| d.open)
+    }
+
+    /// Drops groups that no longer hold any cards, along with the dots on cards and groups that
+    /// are no longer on the board
+    pub fn prune_groups(&mut self) {
+        let cards = &self.cards;
+        self.groups.retain(|g| cards.iter().any(|c| c.group_id.as_ref() == Some(&g.group_id)));
+
+        let Some(mut dot_vote) = self.dot_vote.take() else {
+            return;
+        };
+        for dots in dot_vote.dots.values_mut() {
+            dots.retain(|item_id| self.has_item(item_id));
+        }
+        self.dot_vote = Some(dot_vote);
+    }
+
+    /// The board as seen by a user, only containing their own cards until it is revealed
+    pub fn view_for(&self, user_id: &str) -> RetroBoardDTO {
+        let (visible, hidden): (Vec<&Card>, Vec<&Card>) = self.cards
+            .iter()
+            .partition(|c| self.revealed || c.author == user_id);
+        RetroBoardDTO {
+            revealed: self.revealed,
+            cards: visible.into_iter().cloned().map(Into::into).collect(),
+            hidden_cards: hidden.len() as u32,
+            groups: if self.revealed { self.groups.clone() } else { vec![] },
+            dot_vote: self.dot_vote.as_ref().map(|d| d.view_for(Some(user_id))),
+        }
+    }
+
+    /// The board with every card visible
+    pub fn view(&self) -> RetroBoardDTO {
+        RetroBoardDTO {
+            revealed: self.revealed,
+            cards: self.cards.iter().cloned().map(Into::into).collect(),
+            hidden_cards: 0,
+            groups: self.groups.clone(),
+            dot_vote: self.dot_vote.as_ref().map(|d| d.view_for(None)),
+        }
+    }
+}
+
+impl From<RetroBoard> for RetroBoardDTO {
+    fn from(value: RetroBoard) -> Self {
+        value.view()
+    }
+}
+
+/// A retro board update, sent to the members of its room
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct RoomBoardDTO {
+    pub room: String,
+    pub board: RetroBoardDTO,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub enum ActionStatus {
+    #[default]
+    Open,
+    Done,
+}
+
+/// Something a retro decided to do, kept with the room until it is done
+#[derive(Clone, Debug)]
+pub struct ActionItem {
+    pub action_id: String,
+    pub text: String,
+    pub owner: Option<String>,
+    pub due: Option<NaiveDate>,
+    pub status: ActionStatus,
+    pub created: DateTime<Utc>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct ActionItemDTO {
+    #[serde(rename = "actionID")]
+    pub action_id: String,
+    pub text: String,
+    pub owner: Option<UserDTO>,
+    /// Due date as `YYYY-MM-DD`
+    pub due: Option<String>,
+    pub status: ActionStatus,
+    pub created: String,
+}
+
+impl ActionItem {
+    pub fn into_dto(self, owner: Option<UserDTO>) -> ActionItemDTO {
+        ActionItemDTO {
+            action_id: self.action_id,
+            text: self.text,
+            owner,
+            due: self.due.map(|d| d.to_string()),
+            status: self.status,
+            created: self.created.to_rfc3339(),
+        }
+    }
+}
+
+/// The action items of a room, sent to its members
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct RoomActionsDTO {
+    pub room: String,
+    pub actions: Vec<ActionItemDTO>,
+}
+
+/// The stages a retro moves through, each allowing only some changes to the board and action items
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub enum RetroPhase {
+    #[default]
+    CheckIn,
+    Brainstorm,
+    Group,
+    Vote,
+    Discuss,
+    ActionItems,
+    Close,
+}
+
+impl RetroPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetroPhase::CheckIn => "check-in",
+            RetroPhase::Brainstorm => "brainstorm",
+            RetroPhase::Group => "group",
+            RetroPhase::Vote => "vote",
+            RetroPhase::Discuss => "discuss",
+            RetroPhase::ActionItems => "action-items",
+            RetroPhase::Close => "close",
+        }
+    }
+
+    /// Fails unless the phase is one of the allowed ones
+    pub fn require(self, allowed: &[RetroPhase]) -> Result<(), BoardError> {
+        if !allowed.contains(&self) {
+            return Err(BoardError::WrongPhase(self));
+        }
+        Ok(())
+    }
+
+    /// The phase that follows this one, if any
+    pub fn next(self) -> Option<RetroPhase> {
+        match self {
+            RetroPhase::CheckIn => Some(RetroPhase::Brainstorm),
+            RetroPhase::Brainstorm => Some(RetroPhase::Group),
+            RetroPhase::Group => Some(RetroPhase::Vote),
+            RetroPhase::Vote => Some(RetroPhase::Discuss),
+            RetroPhase::Discuss => Some(RetroPhase::ActionItems),
+            RetroPhase::ActionItems => Some(RetroPhase::Close),
+            RetroPhase::Close => None,
+        }
+    }
+
+    /// The phase before this one that the retro can go back to, if any
+    pub fn previous(self) -> Option<RetroPhase> {
+        match self {
+            RetroPhase::CheckIn | RetroPhase::Close => None,
+            RetroPhase::Brainstorm => Some(RetroPhase::CheckIn),
+            RetroPhase::Group => Some(RetroPhase::Brainstorm),
+            RetroPhase::Vote => Some(RetroPhase::Group),
+            RetroPhase::Discuss => Some(RetroPhase::Vote),
+            RetroPhase::ActionItems => Some(RetroPhase::Discuss),
+        }
+    }
+
+    /// Checks that the retro can move to the given phase, which has to be the next or the
+    /// previous one. A closed retro stays closed until a new round starts the next one.
+    pub fn transition(self, to: RetroPhase) -> Result<RetroPhase, BoardError> {
+        if self == RetroPhase::Close {
+            return Err(BoardError::Closed);
+        }
+        if self.next() != Some(to) && self.previous() != Some(to) {
+            return Err(BoardError::InvalidTransition(self, to));
+        }
+        Ok(to)
+    }
+
+    /// Whether cards are shown to everyone once the retro has reached this phase
+    pub fn reveals_cards(&self) -> bool {
+        !matches!(self, RetroPhase::CheckIn | RetroPhase::Brainstorm)
+    }
+
+    /// Whether dot voting has finished once the retro has reached this phase
+    pub fn ends_dot_vote(&self) -> bool {
+        matches!(self, RetroPhase::Discuss | RetroPhase::ActionItems | RetroPhase::Close)
+    }
+}
+
+impl Display for RetroPhase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The current phase of a room's retro, sent to its members
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct RoomPhaseDTO {
+    pub room: String,
+    pub phase: RetroPhase,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(card_id: &str) -> Card {
+        Card {
+            card_id: card_id.to_owned(),
+            author: "author".to_owned(),
+            column: "went-well".to_owned(),
+            text: card_id.to_owned(),
+            created: Utc::now(),
+            group_id: None,
+            merged: vec![],
+        }
+    }
+
+    fn revealed_board(card_ids: &[&str]) -> RetroBoard {
+        RetroBoard {
+            cards: card_ids.iter().map(|id| card(id)).collect(),
+            revealed: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn phases_only_move_one_step_at_a_time() {
+        assert_eq!(RetroPhase::CheckIn.transition(RetroPhase::Brainstorm).unwrap(), RetroPhase::Brainstorm);
+        assert_eq!(RetroPhase::Vote.transition(RetroPhase::Group).unwrap(), RetroPhase::Group);
+        assert!(matches!(
+            RetroPhase::CheckIn.transition(RetroPhase::Vote),
+            Err(BoardError::InvalidTransition(RetroPhase::CheckIn, RetroPhase::Vote))
+        ));
+        assert!(matches!(
+            RetroPhase::Discuss.transition(RetroPhase::Discuss),
+            Err(BoardError::InvalidTransition(..))
+        ));
+    }
+
+    #[test]
+    fn closed_retro_stays_closed() {
+        assert!(RetroPhase::ActionItems.transition(RetroPhase::Close).is_ok());
+        assert!(matches!(RetroPhase::Close.transition(RetroPhase::ActionItems), Err(BoardError::Closed)));
+        assert!(matches!(RetroPhase::Close.transition(RetroPhase::CheckIn), Err(BoardError::Closed)));
+    }
+
+    #[test]
+    fn grouping_and_moving_cards_drops_empty_groups() {
+        let mut board = revealed_board(&["a", "b"]);
+        let group = board.group_cards("g".to_owned(), "Tooling".to_owned(), &["a".to_owned(), "b".to_owned()]).unwrap();
+        assert_eq!(group.group_id, "g");
+        assert!(board.cards.iter().all(|c| c.group_id.as_deref() == Some("g")));
+
+        board.move_card("a", None).unwrap();
+        assert_eq!(board.groups.len(), 1);
+        board.move_card("b", None).unwrap();
+        assert!(board.groups.is_empty());
+        assert!(matches!(board.move_card("a", Some("g".to_owned())), Err(BoardError::UnknownGroup(_))));
+    }
+
+    #[test]
+    fn merging_moves_dots_to_the_kept_card() {
+        let mut board = revealed_board(&["a", "b", "c"]);
+        board.start_dot_vote(3).unwrap();
+        board.add_dot("alice", "b").unwrap();
+        board.add_dot("bob", "a").unwrap();
+        board.add_dot("bob", "c").unwrap();
+
+        board.merge_cards("a", &["b".to_owned()]).unwrap();
+        assert_eq!(board.cards.len(), 2);
+        assert_eq!(board.cards[0].merged[0].text, "b");
+
+        let tally = board.dot_vote.as_ref().unwrap().tally();
+        assert_eq!(tally[0].item_id, "a");
+        assert_eq!(tally[0].dots, 2);
+        assert!(tally.iter().all(|t| t.item_id != "b"));
+        assert!(matches!(board.merge_cards("a", &["a".to_owned()]), Err(BoardError::SelfMerge)));
+    }
+
+    #[test]
+    fn dot_budget_runs_out() {
+        let mut board = revealed_board(&["a", "b"]);
+        board.start_dot_vote(2).unwrap();
+        board.add_dot("alice", "a").unwrap();
+        board.add_dot("alice", "a").unwrap();
+        assert!(matches!(board.add_dot("alice", "b"), Err(BoardError::OutOfDots(2))));
+
+        board.remove_dot("alice", "a").unwrap();
+        board.add_dot("alice", "b").unwrap();
+        board.end_dot_vote().unwrap();
+        assert!(matches!(board.add_dot("bob", "a"), Err(BoardError::NoDotVote)));
+
+        let tally = board.dot_vote.as_ref().unwrap().tally();
+        assert_eq!(tally.iter().map(|t| t.dots).sum::<u32>(), 2);
+    }
+}
@@ -1,11 +1,69 @@
+use std::collections::BTreeSet;
+use thiserror::Error;
 use ts_rs::TS;
-use crate::state::game::Game;
+use crate::state::game::{Game, GameName};
+use crate::state::round::{CurrentRound, RoundOpts};
+use crate::state::round_type::RoundType;
+use crate::state::template::RetroTemplate;
+use crate::state::vote::Score;
+
+/// The cards a room estimates stories and sizes items with until its facilitator picks others
+pub const DEFAULT_DECK: [&str; 8] = ["1", "2", "3", "5", "8", "13", "20", "40"];
+
+#[derive(Error, Debug)]
+pub enum DeckError {
+    #[error("decks need at least one card")]
+    Empty,
+    #[error("cards cannot be empty")]
+    EmptyCard,
+    #[error("\"{0}\" is in the deck twice")]
+    Duplicate(String),
+    #[error("\"{0}\" cannot be used to estimate stories, cards have to be numbers, \"infinite\", \"coffee\" or \"unknown\"")]
+    NotAnEstimate(String),
+}
+
+/// Trims the cards of a deck and checks that each of them can be voted on when a story is
+/// estimated with it
+pub fn parse_deck(deck: &[String]) -> Result<Vec<String>, DeckError> {
+    let deck: Vec<String> = deck.iter().map(|card| card.trim().to_owned()).collect();
+    if deck.is_empty() {
+        return Err(DeckError::Empty);
+    }
+    if let Some(card) = deck.iter().enumerate().find_map(|(i, card)| deck[..i].contains(card).then_some(card)) {
+        return Err(DeckError::Duplicate(card.clone()));
+    }
+
+    let round_opts = RoundOpts {
+        candidates: deck.clone(),
+        max_votes: 1,
+        anonymous: false,
+        round_type: RoundType::Estimate.to_string(),
+        poll: None,
+    };
+    let round = CurrentRound::new(0, RoundType::Estimate, round_opts);
+    for card in &deck {
+        if card.is_empty() {
+            return Err(DeckError::EmptyCard);
+        }
+        let score: Score = card.parse().map_err(|_| DeckError::NotAnEstimate(card.clone()))?;
+        round.validate_score(&score).map_err(|_| DeckError::NotAnEstimate(card.clone()))?;
+    }
+    Ok(deck)
+}
 
 #[derive(Clone, Debug)]
 pub struct Room {
     pub room_id: String,
     pub name: String,
-    pub game: Game,
+    pub game: &'static dyn Game,
+    /// Users allowed to reveal votes, start rounds and manage the room
+    pub facilitators: BTreeSet<String>,
+    /// Users that have been removed from the room and may not rejoin it
+    pub banned: BTreeSet<String>,
+    /// The columns of the room's retro board, for retro rooms
+    pub template: Option<RetroTemplate>,
+    /// The cards the room's stories and sizing board are estimated with
+    pub deck: Vec<String>,
 }
 
 #[derive(serde::Serialize, Clone, Debug, TS)]
@@ -14,7 +72,10 @@ pub struct RoomDTO {
     #[serde(rename = "roomID")]
     pub room_id: String,
     pub name: String,
-    pub game: Game,
+    pub game: GameName,
+    pub facilitators: Vec<String>,
+    pub template: Option<RetroTemplate>,
+    pub deck: Vec<String>,
 }
 
 impl From<Room> for RoomDTO {
@@ -22,7 +83,66 @@ impl From<Room> for RoomDTO {
         Self {
             room_id: value.room_id, //.as_simple().to_string(),
             name: value.name,
-            game: value.game,
+            game: value.game.name(),
+            facilitators: value.facilitators.into_iter().collect(),
+            template: value.template,
+            deck: value.deck,
+        }
+    }
+}
+
+impl Room {
+    pub fn is_facilitator(&self, user_id: &str) -> bool {
+        self.facilitators.contains(user_id)
+    }
+
+    pub fn is_banned(&self, user_id: &str) -> bool {
+        self.banned.contains(user_id)
+    }
+
+    /// Whether retro ideas can be filed under the column, using the default columns for rooms without a template
+    pub fn has_retro_column(&self, key: &str) -> bool {
+        match &self.template {
+            Some(template) => template.has_column(key),
+            None => RetroTemplate::default().has_column(key),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use crate::state::story::Story;
+    use super::*;
+
+    fn cards(names: &[&str]) -> Vec<String> {
+        names.iter().map(|&n| n.to_owned()).collect()
+    }
+
+    #[test]
+    fn default_deck_is_a_valid_deck() {
+        let deck = DEFAULT_DECK.map(str::to_owned).to_vec();
+        assert_eq!(parse_deck(&deck).unwrap(), deck);
+    }
+
+    #[test]
+    fn non_numeric_decks_are_rejected_so_stories_can_be_voted_on() {
+        let deck = cards(&["XS", "M", "L"]);
+        assert!(matches!(parse_deck(&deck), Err(DeckError::NotAnEstimate(card)) if card == "XS"));
+        assert!(matches!(parse_deck(&cards(&["1", "?"])), Err(DeckError::NotAnEstimate(card)) if card == "?"));
+
+        // Every card of a deck that was accepted can be voted on when a story is estimated with it
+        let deck = parse_deck(&cards(&[" 1", "2", "infinite", "coffee", "unknown"])).unwrap();
+        let story = Story::new("story".to_owned(), "Login page", deck.clone(), 1, Utc::now()).unwrap();
+        for card in &deck {
+            assert!(story.round.validate_score(&card.parse().unwrap()).is_ok(), "{card}");
+        }
+    }
+
+    #[test]
+    fn decks_need_distinct_non_empty_cards() {
+        assert!(matches!(parse_deck(&[]), Err(DeckError::Empty)));
+        assert!(matches!(parse_deck(&cards(&["1", " "])), Err(DeckError::EmptyCard)));
+        assert!(matches!(parse_deck(&cards(&["1", "2", "1"])), Err(DeckError::Duplicate(card)) if card == "1"));
+    }
+}
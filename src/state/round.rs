@@ -1,10 +1,19 @@
+use std::collections::HashSet;
+use chrono::{DateTime, Duration, Utc};
 use ts_rs::TS;
-use super::vote::{Vote, VoteDTO};
+use super::health::CategoryHealth;
+use super::ranking::{CandidatePoints, RunoffRound};
+use super::retro::{RetroBoard, RetroBoardDTO};
+use super::round_type::RoundType;
+use super::vote::{Score, ScoreError, Vote, VoteDTO};
 
 #[derive(Clone, Debug)]
 pub struct Round {
     pub name: String,
     pub votes: Vec<Vote>,
+    pub result: Option<RoundResult>,
+    /// The retro board of the round, if cards were added during it
+    pub board: Option<RetroBoard>,
 }
 
 #[derive(serde::Serialize, Clone, Debug, TS)]
@@ -12,6 +21,8 @@ pub struct Round {
 pub struct RoundDTO {
     pub name: String,
     pub votes: Vec<VoteDTO>,
+    pub result: Option<RoundResult>,
+    pub board: Option<RetroBoardDTO>,
 }
 
 impl From<Round> for RoundDTO {
@@ -19,10 +30,92 @@ impl From<Round> for RoundDTO {
         Self {
             votes: value.votes.iter().cloned().map(Into::into).collect(),
             name: value.name,
+            result: value.result,
+            board: value.board.map(Into::into),
         }
     }
 }
 
+/// Number of votes a single candidate received in a round
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct CandidateTally {
+    pub candidate: String,
+    pub votes: u32,
+}
+
+/// Average share of the percentage votes a single candidate was allocated in a round
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct CandidateAllocation {
+    pub candidate: String,
+    pub average_percent: f32,
+}
+
+/// A member whose confidence vote was low enough to talk about before committing
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct Concern {
+    #[serde(rename = "userID")]
+    pub user_id: String,
+    pub fingers: u8,
+    pub comment: Option<String>,
+}
+
+/// Aggregated votes of a revealed round, shaped by its [RoundType]
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub enum RoundResult {
+    Estimate {
+        average: Option<f32>,
+        tally: Vec<CandidateTally>,
+    },
+    PickOne {
+        tally: Vec<CandidateTally>,
+    },
+    MultiPick {
+        tally: Vec<CandidateTally>,
+    },
+    Percent {
+        allocations: Vec<CandidateAllocation>,
+    },
+    Ranking {
+        /// The instant-runoff winner, if the count didn't end in a tie
+        winner: Option<String>,
+        runoff: Vec<RunoffRound>,
+        borda: Vec<CandidatePoints>,
+    },
+    Retro {
+        tally: Vec<CandidateTally>,
+    },
+    FistOfFive {
+        average: Option<f32>,
+        /// Votes for each number of fingers, from 0 to 5
+        distribution: Vec<CandidateTally>,
+        concerns: Vec<Concern>,
+    },
+    HealthCheck {
+        categories: Vec<CategoryHealth>,
+    },
+    Thumbs {
+        up: u32,
+        sideways: u32,
+        down: u32,
+        /// Whether most of the room wants to keep going
+        keep_going: bool,
+    },
+    Poll {
+        tally: Vec<CandidateTally>,
+        /// Free-text answers, in no particular order
+        answers: Vec<String>,
+    },
+    Sized {
+        /// The card of the deck the item ended up on in relative sizing
+        estimate: String,
+    },
+}
+
 #[derive(Clone, Debug)]
 pub struct CurrentRound {
     pub name: String,
@@ -30,16 +123,82 @@ pub struct CurrentRound {
     pub candidates: Vec<String>,
     pub max_votes: u8,
     pub anonymous: bool,
-    pub round_type: String,
+    pub round_type: RoundType,
+    pub result: Option<RoundResult>,
+    pub poll: Option<Poll>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, TS)]
 #[ts(export, export_to = "client/src/types/ppapi/")]
 pub struct RoundOpts {
     pub candidates: Vec<String>,
+    /// How many candidates can be picked in multi-pick and poll rounds, 0 for no limit
     pub max_votes: u8,
     pub anonymous: bool,
+    /// Parsed into a [RoundType] when the round is created
     pub round_type: String,
+    /// Settings of poll rounds, whose options are the candidates
+    #[serde(default)]
+    #[ts(optional)]
+    pub poll: Option<PollOpts>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct PollOpts {
+    pub question: String,
+    /// Whether members can answer in their own words instead of picking options
+    #[serde(default)]
+    pub free_text: bool,
+    /// Whether results are shown while the poll is still running
+    #[serde(default)]
+    pub live: bool,
+    /// Minutes after which the poll stops taking answers and is revealed
+    #[serde(default)]
+    pub deadline_minutes: Option<u16>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Poll {
+    pub question: String,
+    pub free_text: bool,
+    pub live: bool,
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct PollDTO {
+    pub question: String,
+    pub free_text: bool,
+    pub live: bool,
+    pub deadline: Option<String>,
+}
+
+impl From<Poll> for PollDTO {
+    fn from(value: Poll) -> Self {
+        Self {
+            question: value.question,
+            free_text: value.free_text,
+            live: value.live,
+            deadline: value.deadline.map(|d| d.to_rfc3339()),
+        }
+    }
+}
+
+impl Poll {
+    pub fn new(opts: PollOpts, now: DateTime<Utc>) -> Self {
+        Self {
+            question: opts.question,
+            free_text: opts.free_text,
+            live: opts.live,
+            deadline: opts.deadline_minutes.map(|m| now + Duration::minutes(m as i64)),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.deadline.is_some_and(|d| d <= Utc::now())
+    }
 }
 
 #[derive(serde::Serialize, Clone, Debug, TS)]
@@ -50,7 +209,9 @@ pub struct CurrentRoundDTO {
     pub candidates: Vec<String>,
     pub max_votes: u8,
     pub anonymous: bool,
-    pub round_type: String,
+    pub round_type: RoundType,
+    pub result: Option<RoundResult>,
+    pub poll: Option<PollDTO>,
 }
 
 impl From<CurrentRound> for CurrentRoundDTO {
@@ -62,13 +223,15 @@ impl From<CurrentRound> for CurrentRoundDTO {
             max_votes: value.max_votes,
             anonymous: value.anonymous,
             round_type: value.round_type,
+            result: value.result,
+            poll: value.poll.map(Into::into),
         }
     }
 }
 
 impl CurrentRound {
-    pub fn new(prior_rounds: usize, round_opts: RoundOpts) -> Self {
-        let RoundOpts { candidates, max_votes, anonymous, round_type } = round_opts;
+    pub fn new(prior_rounds: usize, round_type: RoundType, round_opts: RoundOpts) -> Self {
+        let RoundOpts { candidates, max_votes, anonymous, poll, .. } = round_opts;
         Self {
             flipped: false,
             name: format!("Round #{}", prior_rounds + 1),
@@ -76,6 +239,151 @@ impl CurrentRound {
             max_votes,
             anonymous,
             round_type,
+            result: None,
+            poll: poll.map(|p| Poll::new(p, Utc::now())),
         }
     }
-}
\ No newline at end of file
+
+    /// Whether votes are kept from the room until the round is revealed, as in polls without live results
+    pub fn hides_votes(&self) -> bool {
+        !self.flipped && self.poll.as_ref().is_some_and(|p| !p.live)
+    }
+
+    /// Whether the result is updated with every vote, as in polls with live results
+    pub fn shows_live_result(&self) -> bool {
+        !self.flipped && self.poll.as_ref().is_some_and(|p| p.live)
+    }
+
+    /// Checks that a score fits this round's type and only refers to its candidates, if it has any
+    pub fn validate_score(&self, score: &Score) -> Result<(), ScoreError> {
+        self.round_type.validate_score(self, score)
+    }
+
+    /// Flips the round, storing the result its game aggregated from the votes
+    pub fn reveal(&mut self, result: RoundResult) {
+        self.flipped = true;
+        self.result = Some(result);
+    }
+
+    /// Checks that a single score value is one of the round's candidates, if it has any
+    pub(crate) fn validate_candidate(&self, score: &Score) -> Result<(), ScoreError> {
+        let score = score.to_string();
+        if !self.candidates.is_empty() && !self.candidates.contains(&score) {
+            return Err(ScoreError::UnknownCandidate(score));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn validate_poll(&self, score: &Score) -> Result<(), ScoreError> {
+        let free_text = self.poll.as_ref().is_some_and(|p| p.free_text);
+        if self.poll.as_ref().is_some_and(Poll::is_closed) {
+            return Err(ScoreError::PollClosed);
+        }
+        match score {
+            Score::Picks(picks) => self.validate_picks(picks, self.max_votes),
+            Score::Text(_) if !free_text => Err(ScoreError::NoFreeText),
+            Score::Text(text) if text.trim().is_empty() => Err(ScoreError::EmptyAnswer),
+            Score::Text(_) => Ok(()),
+            score if !score.is_multi_value() => self.validate_picks(&[score.to_string()], 1),
+            _ => Err(ScoreError::Unsupported(self.round_type)),
+        }
+    }
+
+    /// Checks the picks of a vote, a `max_picks` of 0 allowing any number of them
+    pub(crate) fn validate_picks(&self, picks: &[String], max_picks: u8) -> Result<(), ScoreError> {
+        if picks.is_empty() {
+            return Err(ScoreError::NoPicks);
+        }
+        if max_picks > 0 && picks.len() > max_picks as usize {
+            return Err(ScoreError::TooManyPicks { max: max_picks, count: picks.len() });
+        }
+        self.validate_candidates(picks.iter())
+    }
+
+    pub(crate) fn validate_candidates<'a>(&self, candidates: impl Iterator<Item = &'a String>) -> Result<(), ScoreError> {
+        let mut seen = HashSet::new();
+        for candidate in candidates {
+            if !self.candidates.contains(candidate) {
+                return Err(ScoreError::UnknownCandidate(candidate.clone()));
+            }
+            if !seen.insert(candidate) {
+                return Err(ScoreError::DuplicateCandidate(candidate.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts the votes for each of the round's candidates
+    pub(crate) fn tally(&self, picks: impl IntoIterator<Item = String>) -> Vec<CandidateTally> {
+        let mut tally: Vec<_> = self.candidates
+            .iter()
+            .map(|candidate| CandidateTally { candidate: candidate.clone(), votes: 0 })
+            .collect();
+        for pick in picks {
+            if let Some(t) = tally.iter_mut().find(|t| t.candidate == pick) {
+                t.votes += 1;
+            }
+        }
+        tally
+    }
+
+    /// Averages the percentage each candidate was allocated across all percentage votes
+    pub(crate) fn allocations<'a>(&self, votes: impl IntoIterator<Item = &'a Vote>) -> Vec<CandidateAllocation> {
+        let mut totals = vec![0u32; self.candidates.len()];
+        let mut voters = 0u32;
+        for vote in votes {
+            let Score::Percent(shares) = &vote.score else {
+                continue;
+            };
+            voters += 1;
+            for (candidate, percent) in shares {
+                if let Some(i) = self.candidates.iter().position(|c| c == candidate) {
+                    totals[i] += *percent as u32;
+                }
+            }
+        }
+        self.candidates
+            .iter()
+            .zip(totals)
+            .map(|(candidate, total)| CandidateAllocation {
+                candidate: candidate.clone(),
+                average_percent: if voters == 0 { 0.0 } else { total as f32 / voters as f32 },
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poll_round(max_votes: u8) -> CurrentRound {
+        let round_opts = RoundOpts {
+            candidates: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()],
+            max_votes,
+            anonymous: false,
+            round_type: RoundType::Poll.to_string(),
+            poll: Some(PollOpts { question: "Lunch?".to_owned(), free_text: false, live: false, deadline_minutes: None }),
+        };
+        CurrentRound::new(0, RoundType::Poll, round_opts)
+    }
+
+    fn picks(names: &[&str]) -> Score {
+        Score::Picks(names.iter().map(|&n| n.to_owned()).collect())
+    }
+
+    #[test]
+    fn poll_with_no_pick_limit_takes_any_number_of_picks() {
+        assert!(poll_round(0).validate_poll(&picks(&["a", "b", "c"])).is_ok());
+    }
+
+    #[test]
+    fn poll_limits_picks_to_max_votes() {
+        let round = poll_round(2);
+        assert!(round.validate_poll(&picks(&["a", "b"])).is_ok());
+        assert!(matches!(
+            round.validate_poll(&picks(&["a", "b", "c"])),
+            Err(ScoreError::TooManyPicks { max: 2, count: 3 })
+        ));
+    }
+}
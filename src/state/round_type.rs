@@ -0,0 +1,161 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use ts_rs::TS;
+use crate::state::game::ParseError;
+use super::lean_coffee::Thumb;
+use super::ranking;
+use super::round::{CandidateTally, CurrentRound, RoundResult};
+use super::vote::{Score, ScoreError, Vote};
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub enum RoundType {
+    Estimate,
+    PickOne,
+    MultiPick,
+    Percent,
+    Ranking,
+    Retro,
+    Thumbs,
+    Poll,
+}
+
+impl RoundType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoundType::Estimate => "estimate",
+            RoundType::PickOne => "pick-one",
+            RoundType::MultiPick => "multi-pick",
+            RoundType::Percent => "percent",
+            RoundType::Ranking => "ranking",
+            RoundType::Retro => "retro",
+            RoundType::Thumbs => "thumbs",
+            RoundType::Poll => "poll",
+        }
+    }
+
+    /// Checks that a score is valid for a round of this type
+    pub fn validate_score(&self, round: &CurrentRound, score: &Score) -> Result<(), ScoreError> {
+        match (self, score) {
+            (RoundType::Estimate, Score::Number(_) | Score::Infinite | Score::Coffee | Score::Unknown) => {
+                round.validate_candidate(score)
+            }
+            (RoundType::PickOne, Score::Picks(picks)) => round.validate_picks(picks, 1),
+            (RoundType::MultiPick, Score::Picks(picks)) => round.validate_picks(picks, round.max_votes),
+            (RoundType::PickOne | RoundType::MultiPick, score) if !score.is_multi_value() => {
+                round.validate_picks(&[score.to_string()], 1)
+            }
+            (RoundType::Percent, Score::Percent(shares)) => {
+                round.validate_candidates(shares.iter().map(|(candidate, _)| candidate))?;
+                let sum = shares.iter().map(|(_, percent)| *percent as u32).sum();
+                if sum != 100 {
+                    return Err(ScoreError::PercentSum(sum));
+                }
+                Ok(())
+            }
+            (RoundType::Ranking, Score::Ranking(ranking)) => {
+                round.validate_candidates(ranking.iter())?;
+                if ranking.len() != round.candidates.len() {
+                    return Err(ScoreError::IncompleteRanking);
+                }
+                Ok(())
+            }
+            (RoundType::Retro, Score::Idea { .. }) => Ok(()),
+            (RoundType::Thumbs, Score::Thumb(_)) => Ok(()),
+            (RoundType::Poll, score) => round.validate_poll(score),
+            _ => Err(ScoreError::Unsupported(*self)),
+        }
+    }
+
+    /// Aggregates the votes of a round of this type
+    pub fn aggregate<'a>(&self, round: &CurrentRound, votes: impl IntoIterator<Item = &'a Vote>) -> RoundResult {
+        let votes: Vec<&Vote> = votes.into_iter().collect();
+        let picks = || votes.iter().flat_map(|v| match &v.score {
+            Score::Picks(picks) => picks.clone(),
+            score if !score.is_multi_value() => vec![score.to_string()],
+            _ => vec![],
+        });
+        match self {
+            RoundType::Estimate => {
+                let numbers: Vec<f32> = votes.iter()
+                    .filter_map(|v| match v.score {
+                        Score::Number(n) => Some(n as f32),
+                        _ => None,
+                    })
+                    .collect();
+                let average = (!numbers.is_empty())
+                    .then(|| numbers.iter().sum::<f32>() / numbers.len() as f32);
+                RoundResult::Estimate { average, tally: count_distinct(votes.iter().map(|v| v.score.to_string())) }
+            }
+            RoundType::PickOne => RoundResult::PickOne { tally: round.tally(picks()) },
+            RoundType::MultiPick => RoundResult::MultiPick { tally: round.tally(picks()) },
+            RoundType::Percent => RoundResult::Percent { allocations: round.allocations(votes) },
+            RoundType::Ranking => {
+                let ballots: Vec<&Vec<String>> = votes.iter()
+                    .filter_map(|v| match &v.score {
+                        Score::Ranking(ranking) => Some(ranking),
+                        _ => None,
+                    })
+                    .collect();
+                let (winner, runoff) = ranking::instant_runoff(&round.candidates, &ballots);
+                RoundResult::Ranking { winner, runoff, borda: ranking::borda(&round.candidates, &ballots) }
+            }
+            RoundType::Retro => RoundResult::Retro {
+                tally: count_distinct(votes.iter().filter_map(|v| match &v.score {
+                    Score::Idea { column, .. } => Some(column.clone()),
+                    _ => None,
+                })),
+            },
+            RoundType::Thumbs => {
+                let count = |thumb| votes.iter().filter(|v| matches!(v.score, Score::Thumb(t) if t == thumb)).count() as u32;
+                let (up, sideways, down) = (count(Thumb::Up), count(Thumb::Sideways), count(Thumb::Down));
+                RoundResult::Thumbs { up, sideways, down, keep_going: up > sideways + down }
+            }
+            RoundType::Poll => RoundResult::Poll {
+                tally: round.tally(picks()),
+                answers: votes.iter().filter_map(|v| match &v.score {
+                    Score::Text(text) => Some(text.clone()),
+                    _ => None,
+                }).collect(),
+            },
+        }
+    }
+}
+
+/// Counts how many times each distinct value occurs, most common first
+fn count_distinct(values: impl Iterator<Item = String>) -> Vec<CandidateTally> {
+    let mut tally: Vec<CandidateTally> = vec![];
+    for value in values {
+        match tally.iter_mut().find(|t| t.candidate == value) {
+            Some(t) => t.votes += 1,
+            None => tally.push(CandidateTally { candidate: value, votes: 1 }),
+        }
+    }
+    tally.sort_by_key(|t| std::cmp::Reverse(t.votes));
+    tally
+}
+
+impl FromStr for RoundType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "estimate" => Ok(RoundType::Estimate),
+            "pick-one" => Ok(RoundType::PickOne),
+            "multi-pick" => Ok(RoundType::MultiPick),
+            "percent" => Ok(RoundType::Percent),
+            "ranking" => Ok(RoundType::Ranking),
+            "retro" => Ok(RoundType::Retro),
+            "thumbs" => Ok(RoundType::Thumbs),
+            "poll" => Ok(RoundType::Poll),
+            _ => Err(ParseError::UnknownVariant(s.into()))
+        }
+    }
+}
+
+impl Display for RoundType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,250 @@
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use ts_rs::TS;
+use crate::state::Round;
+use crate::state::round::RoundResult;
+
+#[derive(Error, Debug)]
+pub enum SizingError {
+    #[error("item with ID \"{0}\" could not be found")]
+    UnknownItem(String),
+    #[error("\"{0}\" is not in the room's deck")]
+    UnknownBucket(String),
+    #[error("only the author of an item or a facilitator can remove it")]
+    NotAuthor,
+    #[error("items cannot be empty")]
+    EmptyTitle,
+    #[error("no items have been put into a bucket yet")]
+    NothingSized,
+}
+
+#[derive(Clone, Debug)]
+pub struct SizingItem {
+    pub item_id: String,
+    pub author: String,
+    pub title: String,
+    pub created: DateTime<Utc>,
+    /// The card of the deck the item was put on, if it was moved onto the scale yet
+    pub bucket: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct SizingItemDTO {
+    #[serde(rename = "itemID")]
+    pub item_id: String,
+    pub author: String,
+    pub title: String,
+    pub created: String,
+}
+
+impl From<SizingItem> for SizingItemDTO {
+    fn from(value: SizingItem) -> Self {
+        Self {
+            item_id: value.item_id,
+            author: value.author,
+            title: value.title,
+            created: value.created.to_rfc3339(),
+        }
+    }
+}
+
+/// The items on a card of the deck, in the order the room put them
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct SizingBucketDTO {
+    pub candidate: String,
+    pub items: Vec<SizingItemDTO>,
+}
+
+/// A shared scale that members silently move items onto, relative to each other. The order of
+/// [SizingBoard::items] is the order of the items within their buckets.
+#[derive(Clone, Debug)]
+pub struct SizingBoard {
+    pub deck: Vec<String>,
+    pub items: Vec<SizingItem>,
+}
+
+impl SizingBoard {
+    /// An empty board with a bucket for each card of the deck
+    pub fn new(deck: Vec<String>) -> Self {
+        Self { deck, items: vec![] }
+    }
+
+    pub fn add(&mut self, item_id: String, author: String, title: &str, now: DateTime<Utc>) -> Result<&SizingItem, SizingError> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(SizingError::EmptyTitle);
+        }
+        let index = self.items.len();
+        self.items.push(SizingItem { item_id, author, title: title.to_owned(), created: now, bucket: None });
+        Ok(&self.items[index])
+    }
+
+    fn position(&self, item_id: &str) -> Result<usize, SizingError> {
+        self.items
+            .iter()
+            .position(|i| i.item_id == item_id)
+            .ok_or_else(|| SizingError::UnknownItem(item_id.to_owned()))
+    }
+
+    /// Puts the item into a bucket, or back off the scale, at the given index among the items there
+    pub fn move_item(&mut self, item_id: &str, bucket: Option<String>, index: usize) -> Result<(), SizingError> {
+        if let Some(bucket) = bucket.as_ref().filter(|b| !self.deck.contains(b)) {
+            return Err(SizingError::UnknownBucket(bucket.clone()));
+        }
+        let mut item = self.items.remove(self.position(item_id)?);
+        item.bucket = bucket;
+
+        let position = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| i.bucket == item.bucket)
+            .map(|(position, _)| position)
+            .nth(index)
+            .unwrap_or(self.items.len());
+        self.items.insert(position, item);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, item_id: &str, user_id: &str, is_facilitator: bool) -> Result<(), SizingError> {
+        let position = self.position(item_id)?;
+        if self.items[position].author != user_id && !is_facilitator {
+            return Err(SizingError::NotAuthor);
+        }
+        self.items.remove(position);
+        Ok(())
+    }
+
+    /// Takes the items that were put into a bucket off the board, each as a round estimated at
+    /// its bucket
+    pub fn finish(&mut self) -> Result<Vec<Round>, SizingError> {
+        let (sized, unplaced): (Vec<_>, Vec<_>) = self.items.drain(..).partition(|i| i.bucket.is_some());
+        self.items = unplaced;
+        if sized.is_empty() {
+            return Err(SizingError::NothingSized);
+        }
+
+        // Rounds follow the scale, smallest bucket first
+        let mut sized: Vec<_> = sized
+            .into_iter()
+            .filter_map(|i| Some((self.deck.iter().position(|c| Some(c) == i.bucket.as_ref())?, i)))
+            .collect();
+        sized.sort_by_key(|(bucket, _)| *bucket);
+        Ok(sized
+            .into_iter()
+            .map(|(bucket, item)| Round {
+                name: item.title,
+                votes: vec![],
+                result: Some(RoundResult::Sized { estimate: self.deck[bucket].clone() }),
+                board: None,
+            })
+            .collect())
+    }
+
+    pub fn view(&self, room: &str) -> SizingBoardDTO {
+        let in_bucket = |bucket: Option<&String>| {
+            self.items.iter().filter(|i| i.bucket.as_ref() == bucket).cloned().map(Into::into).collect()
+        };
+        SizingBoardDTO {
+            room: room.to_owned(),
+            unplaced: in_bucket(None),
+            buckets: self
+                .deck
+                .iter()
+                .map(|candidate| SizingBucketDTO { candidate: candidate.clone(), items: in_bucket(Some(candidate)) })
+                .collect(),
+        }
+    }
+}
+
+/// The sizing board of a room, sent to its members after every move
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct SizingBoardDTO {
+    pub room: String,
+    /// Items that are not on the scale yet
+    pub unplaced: Vec<SizingItemDTO>,
+    pub buckets: Vec<SizingBucketDTO>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(items: &[&str]) -> SizingBoard {
+        let mut board = SizingBoard::new(vec!["S".to_owned(), "M".to_owned(), "L".to_owned()]);
+        for item in items {
+            board.add(item.to_string(), "author".to_owned(), item, DateTime::from_timestamp(0, 0).unwrap()).unwrap();
+        }
+        board
+    }
+
+    fn bucket(card: &str) -> Option<String> {
+        Some(card.to_owned())
+    }
+
+    fn in_bucket<'a>(board: &'a SizingBoard, bucket: Option<&str>) -> Vec<&'a str> {
+        board
+            .items
+            .iter()
+            .filter(|i| i.bucket.as_deref() == bucket)
+            .map(|i| i.item_id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn move_item_puts_items_at_the_index_within_their_bucket() {
+        let mut board = board(&["a", "b", "c"]);
+        board.move_item("a", bucket("M"), 0).unwrap();
+        board.move_item("b", bucket("M"), 0).unwrap();
+        board.move_item("c", bucket("M"), 1).unwrap();
+        assert_eq!(in_bucket(&board, Some("M")), ["b", "c", "a"]);
+
+        board.move_item("a", None, 0).unwrap();
+        assert_eq!(in_bucket(&board, Some("M")), ["b", "c"]);
+        assert_eq!(in_bucket(&board, None), ["a"]);
+    }
+
+    #[test]
+    fn move_item_appends_past_the_end_of_a_bucket() {
+        let mut board = board(&["a", "b"]);
+        board.move_item("a", bucket("S"), 0).unwrap();
+        board.move_item("b", bucket("S"), 10).unwrap();
+        assert_eq!(in_bucket(&board, Some("S")), ["a", "b"]);
+    }
+
+    #[test]
+    fn move_item_rejects_unknown_buckets_and_items() {
+        let mut board = board(&["a"]);
+        assert!(matches!(board.move_item("a", bucket("XL"), 0), Err(SizingError::UnknownBucket(_))));
+        assert!(matches!(board.move_item("z", bucket("S"), 0), Err(SizingError::UnknownItem(_))));
+        assert_eq!(in_bucket(&board, None), ["a"]);
+    }
+
+    #[test]
+    fn finish_sizes_placed_items_smallest_bucket_first_and_keeps_the_rest() {
+        let mut board = board(&["a", "b", "c"]);
+        board.move_item("a", bucket("L"), 0).unwrap();
+        board.move_item("b", bucket("S"), 0).unwrap();
+
+        let rounds = board.finish().unwrap();
+        let sized: Vec<_> = rounds
+            .iter()
+            .map(|r| match &r.result {
+                Some(RoundResult::Sized { estimate }) => (r.name.as_str(), estimate.as_str()),
+                other => panic!("unexpected result {other:?}"),
+            })
+            .collect();
+        assert_eq!(sized, [("b", "S"), ("a", "L")]);
+        assert_eq!(in_bucket(&board, None), ["c"]);
+    }
+
+    #[test]
+    fn finish_needs_a_sized_item() {
+        let mut board = board(&["a"]);
+        assert!(matches!(board.finish(), Err(SizingError::NothingSized)));
+        assert_eq!(board.items.len(), 1);
+    }
+}
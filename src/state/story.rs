@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use thiserror::Error;
+use ts_rs::TS;
+use crate::state::{Round, Vote};
+use crate::state::round::{CurrentRound, CurrentRoundDTO, RoundOpts};
+use crate::state::round_type::RoundType;
+use crate::state::vote::VoteDTO;
+
+/// How long a story stays open when the facilitator doesn't give it a due date
+pub const DEFAULT_DUE_HOURS: u16 = 72;
+
+#[derive(Error, Debug)]
+pub enum StoryError {
+    #[error("story with ID \"{0}\" could not be found")]
+    UnknownStory(String),
+    #[error("story \"{0}\" has already been revealed")]
+    Revealed(String),
+    #[error("you have not voted on this story")]
+    NoVote,
+    #[error("stories cannot be empty")]
+    EmptyTitle,
+    #[error("stories need to be open for at least an hour")]
+    NoTime,
+}
+
+/// A story estimated asynchronously, open for votes until every member voted or it is due
+#[derive(Clone, Debug)]
+pub struct Story {
+    pub story_id: String,
+    /// The estimate round of the story, named after it and with the room's deck as candidates
+    pub round: CurrentRound,
+    pub opened: DateTime<Utc>,
+    pub due: DateTime<Utc>,
+    pub votes: HashMap<String, Vote>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct StoryDTO {
+    #[serde(rename = "storyID")]
+    pub story_id: String,
+    pub round: CurrentRoundDTO,
+    pub opened: String,
+    pub due: String,
+    /// Scores are hidden until the story is revealed
+    pub votes: Vec<VoteDTO>,
+}
+
+impl Story {
+    pub fn new(story_id: String, title: &str, deck: Vec<String>, hours: u16,
+               now: DateTime<Utc>) -> Result<Self, StoryError> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(StoryError::EmptyTitle);
+        }
+        if hours == 0 {
+            return Err(StoryError::NoTime);
+        }
+        let round_opts = RoundOpts {
+            candidates: deck,
+            max_votes: 1,
+            anonymous: false,
+            round_type: RoundType::Estimate.to_string(),
+            poll: None,
+        };
+        let mut round = CurrentRound::new(0, RoundType::Estimate, round_opts);
+        round.name = title.to_owned();
+        Ok(Self {
+            story_id,
+            round,
+            opened: now,
+            due: now + Duration::hours(hours as i64),
+            votes: HashMap::new(),
+        })
+    }
+
+    /// Whether every one of the given voters has voted on the story
+    pub fn is_complete(&self, voters: &[String]) -> bool {
+        !voters.is_empty() && voters.iter().all(|v| self.votes.contains_key(v))
+    }
+
+    /// The story as it is kept in the room's round history once revealed
+    pub fn to_round(&self) -> Round {
+        Round {
+            name: self.round.name.clone(),
+            votes: self.votes.values().cloned().collect(),
+            result: self.round.result.clone(),
+            board: None,
+        }
+    }
+
+    pub fn view(&self) -> StoryDTO {
+        let votes = self.votes.values().cloned();
+        StoryDTO {
+            story_id: self.story_id.clone(),
+            round: self.round.clone().into(),
+            opened: self.opened.to_rfc3339(),
+            due: self.due.to_rfc3339(),
+            votes: if self.round.flipped {
+                votes.map(Into::into).collect()
+            } else {
+                votes.map(|v| VoteDTO::hidden(v.user_id)).collect()
+            },
+        }
+    }
+}
+
+/// The stories of an asynchronous estimation room
+#[derive(Clone, Debug, Default)]
+pub struct Stories {
+    pub stories: Vec<Story>,
+}
+
+impl Stories {
+    /// The story if it is still open for votes
+    pub fn open_mut(&mut self, story_id: &str) -> Result<&mut Story, StoryError> {
+        let story = self
+            .stories
+            .iter_mut()
+            .find(|s| s.story_id == story_id)
+            .ok_or_else(|| StoryError::UnknownStory(story_id.to_owned()))?;
+        if story.round.flipped {
+            return Err(StoryError::Revealed(story.round.name.clone()));
+        }
+        Ok(story)
+    }
+
+    pub fn retract(&mut self, user_id: &str, story_id: &str) -> Result<(), StoryError> {
+        self.open_mut(story_id)?.votes.remove(user_id).map(|_| ()).ok_or(StoryError::NoVote)
+    }
+
+    pub fn view(&self, room: &str) -> StoriesDTO {
+        let mut open: Vec<&Story> = self.stories.iter().filter(|s| !s.round.flipped).collect();
+        open.sort_by_key(|s| s.due);
+        StoriesDTO {
+            room: room.to_owned(),
+            open: open.into_iter().map(Story::view).collect(),
+            revealed: self.stories.iter().filter(|s| s.round.flipped).map(Story::view).collect(),
+        }
+    }
+}
+
+/// The stories of an asynchronous estimation room, the ones due soonest first
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct StoriesDTO {
+    pub room: String,
+    pub open: Vec<StoryDTO>,
+    pub revealed: Vec<StoryDTO>,
+}
@@ -0,0 +1,151 @@
+use std::str::FromStr;
+use thiserror::Error;
+use ts_rs::TS;
+use crate::state::game::ParseError;
+
+#[derive(Error, Debug)]
+pub enum TemplateError {
+    #[error("a retro template needs at least one column")]
+    NoColumns,
+    #[error("column key \"{0}\" may only contain lowercase letters, digits and dashes")]
+    InvalidKey(String),
+    #[error("column key \"{0}\" is used more than once")]
+    DuplicateKey(String),
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct RetroColumn {
+    pub key: String,
+    pub title: String,
+    pub emoji: String,
+}
+
+/// The columns of a retro board
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct RetroTemplate {
+    pub name: String,
+    pub columns: Vec<RetroColumn>,
+}
+
+impl RetroTemplate {
+    fn builtin(name: &str, columns: &[(&str, &str, &str)]) -> Self {
+        Self {
+            name: name.to_owned(),
+            columns: columns
+                .iter()
+                .map(|(key, title, emoji)| RetroColumn {
+                    key: key.to_string(),
+                    title: title.to_string(),
+                    emoji: emoji.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn has_column(&self, key: &str) -> bool {
+        self.columns.iter().any(|c| c.key == key)
+    }
+
+    /// Checks that a user-defined template has usable column keys
+    pub fn validate(&self) -> Result<(), TemplateError> {
+        if self.columns.is_empty() {
+            return Err(TemplateError::NoColumns);
+        }
+        for (i, column) in self.columns.iter().enumerate() {
+            let key = &column.key;
+            let valid = !key.is_empty()
+                && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+            if !valid {
+                return Err(TemplateError::InvalidKey(key.clone()));
+            }
+            if self.columns[..i].iter().any(|c| &c.key == key) {
+                return Err(TemplateError::DuplicateKey(key.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for RetroTemplate {
+    fn default() -> Self {
+        Self::builtin("start-stop-continue", &[
+            ("start", "Start", "🚀"),
+            ("stop", "Stop", "🛑"),
+            ("continue", "Continue", "🔁"),
+        ])
+    }
+}
+
+impl FromStr for RetroTemplate {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "start-stop-continue" => Ok(Self::default()),
+            "mad-sad-glad" => Ok(Self::builtin("mad-sad-glad", &[
+                ("mad", "Mad", "😠"),
+                ("sad", "Sad", "😢"),
+                ("glad", "Glad", "😊"),
+            ])),
+            "4ls" => Ok(Self::builtin("4ls", &[
+                ("liked", "Liked", "👍"),
+                ("learned", "Learned", "💡"),
+                ("lacked", "Lacked", "🕳️"),
+                ("longed-for", "Longed for", "🌠"),
+            ])),
+            "sailboat" => Ok(Self::builtin("sailboat", &[
+                ("wind", "Wind", "💨"),
+                ("anchors", "Anchors", "⚓"),
+                ("rocks", "Rocks", "🪨"),
+                ("island", "Island", "🏝️"),
+            ])),
+            "kalm" => Ok(Self::builtin("kalm", &[
+                ("keep", "Keep", "✅"),
+                ("add", "Add", "➕"),
+                ("less", "Less", "🔽"),
+                ("more", "More", "🔼"),
+            ])),
+            _ => Err(ParseError::UnknownVariant(s.into()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(keys: &[&str]) -> RetroTemplate {
+        RetroTemplate {
+            name: "custom".to_owned(),
+            columns: keys
+                .iter()
+                .map(|&key| RetroColumn { key: key.to_owned(), title: key.to_owned(), emoji: String::new() })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn builtin_templates_are_valid() {
+        for name in ["start-stop-continue", "Mad-Sad-Glad", "4ls", "sailboat", "kalm"] {
+            let template: RetroTemplate = name.parse().unwrap();
+            assert!(template.validate().is_ok(), "{name}");
+        }
+        assert!("unknown".parse::<RetroTemplate>().is_err());
+    }
+
+    #[test]
+    fn accepts_any_well_formed_keys() {
+        assert!(template(&["picks", "1", "went-well"]).validate().is_ok());
+        assert!(template(&["went-well"]).has_column("went-well"));
+    }
+
+    #[test]
+    fn rejects_unusable_columns() {
+        assert!(matches!(template(&[]).validate(), Err(TemplateError::NoColumns)));
+        assert!(matches!(template(&["Went well"]).validate(), Err(TemplateError::InvalidKey(_))));
+        assert!(matches!(template(&[""]).validate(), Err(TemplateError::InvalidKey(_))));
+        assert!(matches!(template(&["a", "b", "a"]).validate(), Err(TemplateError::DuplicateKey(k)) if k == "a"));
+    }
+}
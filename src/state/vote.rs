@@ -1,8 +1,56 @@
-use std::fmt::{Display, Formatter, Octal, Pointer, Write, write};
-use std::num::ParseIntError;
+use std::fmt::{Display, Formatter, Write};
 use std::str::FromStr;
+use thiserror::Error;
 use ts_rs::TS;
-use crate::state::game::ParseError;
+use crate::state::game::{GameName, ParseError};
+use crate::state::health::CategoryVote;
+use crate::state::lean_coffee::Thumb;
+use crate::state::round_type::RoundType;
+
+/// Separates the candidates of a multi-value score, e.g. `picks: a|b`
+pub const CANDIDATE_SEPARATOR: char = '|';
+
+/// Separates a candidate from its value, e.g. `percent: a=40|b=60`
+pub const VALUE_SEPARATOR: char = '=';
+
+/// Sent in place of the scores of rounds that hide votes until they are revealed
+pub const HIDDEN_SCORE: &str = "hidden";
+
+#[derive(Error, Debug)]
+pub enum ScoreError {
+    #[error("\"{0}\" is not a candidate in this round")]
+    UnknownCandidate(String),
+    #[error("\"{0}\" was picked more than once")]
+    DuplicateCandidate(String),
+    #[error("at most {max} candidates can be picked, got {count}")]
+    TooManyPicks { max: u8, count: usize },
+    #[error("at least one candidate must be picked")]
+    NoPicks,
+    #[error("percentages must add up to 100, got {0}")]
+    PercentSum(u32),
+    #[error("every candidate must be ranked")]
+    IncompleteRanking,
+    #[error("this score cannot be used in a {0} round")]
+    Unsupported(RoundType),
+    #[error("the retro template has no \"{0}\" column")]
+    UnknownColumn(String),
+    #[error("scores must be between 0 and {max}, got {value}")]
+    OutOfRange { max: u8, value: u8 },
+    #[error("this score cannot be used in {0} rooms")]
+    UnsupportedByGame(GameName),
+    #[error("\"{0}\" is not a health check category")]
+    UnknownCategory(String),
+    #[error("\"{0}\" was rated more than once")]
+    DuplicateCategory(String),
+    #[error("\"{0}\" has not been rated")]
+    MissingCategory(String),
+    #[error("the poll has closed")]
+    PollClosed,
+    #[error("this poll does not take free-text answers")]
+    NoFreeText,
+    #[error("answers cannot be empty")]
+    EmptyAnswer,
+}
 
 #[derive(Clone, Debug)]
 pub enum Score {
@@ -10,25 +58,70 @@ pub enum Score {
     Coffee,
     Unknown,
     Number(u8),
-    StartIdea(String),
-    StopIdea(String),
-    ContinueIdea(String),
+    /// A number with an explanation, e.g. `2: the API is not ready yet`
+    Commented { value: u8, comment: String },
+    /// An idea for one of the columns of a retro template, e.g. `idea: start: pairing`
+    Idea { column: String, text: String },
+    Picks(Vec<String>),
+    Percent(Vec<(String, u8)>),
+    Ranking(Vec<String>),
+    /// A free-text poll answer, e.g. `text: the pizza place`
+    Text(String),
+    /// Whether to keep discussing a Lean Coffee topic, e.g. `up`
+    Thumb(Thumb),
+    /// A rating of every health check category, e.g. `health: fun=green/up|speed=red/flat`
+    Health(Vec<CategoryVote>),
+}
+
+impl Score {
+    /// Whether the score holds values for several candidates
+    pub fn is_multi_value(&self) -> bool {
+        matches!(self, Score::Picks(_) | Score::Percent(_) | Score::Ranking(_) | Score::Health(_))
+    }
 }
 
 impl FromStr for Score {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let commented = s
+            .split_once(": ")
+            .and_then(|(value, comment)| Some((value.parse::<u8>().ok()?, comment)));
         if let Ok(n) = s.parse::<u8>() {
             Ok(Score::Number(n))
+        } else if let Some((value, comment)) = commented {
+            Ok(Score::Commented { value, comment: comment.to_owned() })
+        } else if let Ok(thumb) = s.parse() {
+            Ok(Score::Thumb(thumb))
         } else {
             match (s, s.split_once(": ")) {
                 ("infinite", _) => Ok(Score::Infinite),
                 ("coffee", _) => Ok(Score::Coffee),
                 ("unknown", _) => Ok(Score::Unknown),
-                (_, Some(("continue", m))) => Ok(Score::ContinueIdea(m.to_owned())),
-                (_, Some(("stop", m))) => Ok(Score::StopIdea(m.to_owned())),
-                (_, Some(("start", m))) => Ok(Score::StartIdea(m.to_owned())),
+                (_, Some(("picks", m))) => Ok(Score::Picks(split_candidates(m))),
+                (_, Some(("ranking", m))) => Ok(Score::Ranking(split_candidates(m))),
+                (_, Some(("text", m))) => Ok(Score::Text(m.to_owned())),
+                (_, Some(("health", m))) => split_candidates(m)
+                    .iter()
+                    .map(|c| c.parse())
+                    .collect::<Result<_, _>>()
+                    .map(Score::Health),
+                (_, Some(("percent", m))) => m
+                    .split(CANDIDATE_SEPARATOR)
+                    .filter(|c| !c.trim().is_empty())
+                    .map(|c| {
+                        c.rsplit_once(VALUE_SEPARATOR)
+                            .and_then(|(candidate, percent)| {
+                                Some((candidate.trim().to_owned(), percent.trim().parse().ok()?))
+                            })
+                            .ok_or_else(|| ParseError::UnknownVariant(s.to_owned()))
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Score::Percent),
+                (_, Some(("idea", m))) => m
+                    .split_once(": ")
+                    .map(|(column, text)| Score::Idea { column: column.to_owned(), text: text.to_owned() })
+                    .ok_or_else(|| ParseError::UnknownVariant(s.to_owned())),
                 _ => Err(ParseError::UnknownVariant(s.to_owned())),
             }
         }
@@ -42,11 +135,58 @@ impl Display for Score {
             Score::Coffee => f.write_str("coffee"),
             Score::Unknown => f.write_str("unknown"),
             Score::Number(n) => std::fmt::Display::fmt(n, f),
-            Score::ContinueIdea(s) => f.write_fmt(format_args!("continue: {s}")),
-            Score::StartIdea(s) => f.write_fmt(format_args!("start: {s}")),
-            Score::StopIdea(s) => f.write_fmt(format_args!("stop: {s}")),
+            Score::Commented { value, comment } => f.write_fmt(format_args!("{value}: {comment}")),
+            Score::Thumb(thumb) => Display::fmt(thumb, f),
+            Score::Text(text) => f.write_fmt(format_args!("text: {text}")),
+            Score::Idea { column, text } => f.write_fmt(format_args!("idea: {column}: {text}")),
+            Score::Picks(picks) => {
+                f.write_str("picks: ")?;
+                write_candidates(f, picks)
+            }
+            Score::Ranking(ranking) => {
+                f.write_str("ranking: ")?;
+                write_candidates(f, ranking)
+            }
+            Score::Percent(shares) => {
+                f.write_str("percent: ")?;
+                for (i, (candidate, percent)) in shares.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(CANDIDATE_SEPARATOR)?;
+                    }
+                    f.write_fmt(format_args!("{candidate}{VALUE_SEPARATOR}{percent}"))?;
+                }
+                Ok(())
+            }
+            Score::Health(votes) => {
+                f.write_str("health: ")?;
+                for (i, vote) in votes.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(CANDIDATE_SEPARATOR)?;
+                    }
+                    Display::fmt(vote, f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn split_candidates(s: &str) -> Vec<String> {
+    s.split(CANDIDATE_SEPARATOR)
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn write_candidates(f: &mut Formatter<'_>, candidates: &[String]) -> std::fmt::Result {
+    for (i, candidate) in candidates.iter().enumerate() {
+        if i > 0 {
+            f.write_char(CANDIDATE_SEPARATOR)?;
         }
+        f.write_str(candidate)?;
     }
+    Ok(())
 }
 
 #[derive(Clone, Debug)]
@@ -63,6 +203,13 @@ pub struct VoteDTO {
     pub score: String,
 }
 
+impl VoteDTO {
+    /// Shows that the user voted without showing their score
+    pub fn hidden(user_id: String) -> Self {
+        Self { user_id, score: HIDDEN_SCORE.to_owned() }
+    }
+}
+
 impl From<Vote> for VoteDTO {
     fn from(value: Vote) -> Self {
         Self {
@@ -70,4 +217,53 @@ impl From<Vote> for VoteDTO {
             user_id: value.user_id,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Reminder sent to a member that hasn't voted in the current round yet
+#[derive(serde::Serialize, Clone, Debug, TS)]
+#[ts(export, export_to = "client/src/types/ppapi/")]
+pub struct NudgeDTO {
+    pub room: String,
+    pub round: String,
+    #[serde(rename = "fromUserID")]
+    pub from_user_id: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Score {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_numbers_and_special_cards() {
+        assert!(matches!(parse("5"), Score::Number(5)));
+        assert!(matches!(parse("coffee"), Score::Coffee));
+        assert!(matches!(parse("up"), Score::Thumb(Thumb::Up)));
+        assert!(matches!(parse("3: blocked by the API"), Score::Commented { value: 3, ref comment } if comment == "blocked by the API"));
+    }
+
+    #[test]
+    fn parses_multi_value_scores() {
+        assert!(matches!(parse("picks: a | b|"), Score::Picks(ref p) if p == &["a", "b"]));
+        assert!(matches!(parse("percent: a=40|b=60"), Score::Percent(ref p) if p == &[("a".to_owned(), 40), ("b".to_owned(), 60)]));
+        assert!("percent: a=forty".parse::<Score>().is_err());
+        assert!(matches!(parse("health: fun=green/up|speed=red"), Score::Health(ref h) if h.len() == 2));
+    }
+
+    #[test]
+    fn only_parses_ideas_with_their_prefix() {
+        assert!(matches!(parse("idea: start: pairing: daily"), Score::Idea { ref column, ref text } if column == "start" && text == "pairing: daily"));
+        assert!("start: pairing".parse::<Score>().is_err());
+        assert!("idea: pairing".parse::<Score>().is_err());
+    }
+
+    #[test]
+    fn displays_scores_as_they_are_parsed() {
+        for s in ["5", "3: blocked", "text: pizza", "idea: stop: meetings", "picks: a|b", "percent: a=40|b=60"] {
+            assert_eq!(parse(s).to_string(), s);
+        }
+    }
+}